@@ -19,7 +19,13 @@ mod aoc_2016;
 mod aoc_2017;
 mod aoc_2018;
 mod aoc_2019;
+mod aoc_2020;
+mod aoc_2021;
 mod aoc_2022;
+mod aoc_2023;
+mod aoc_2024;
+mod aoc_2025;
+mod common;
 
 pub static DAYS: &[Day] = &[
     aoc_2001::DAY,
@@ -41,5 +47,10 @@ pub static DAYS: &[Day] = &[
     aoc_2017::DAY,
     aoc_2018::DAY,
     aoc_2019::DAY,
+    aoc_2020::DAY,
+    aoc_2021::DAY,
     aoc_2022::DAY,
+    aoc_2023::DAY,
+    aoc_2024::DAY,
+    aoc_2025::DAY,
 ];
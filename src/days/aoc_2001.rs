@@ -1,3 +1,9 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    num::ParseIntError,
+};
+
 use aoc_lib::{Bench, BenchResult, Day, UserError};
 use color_eyre::eyre::{eyre, Result};
 
@@ -6,62 +12,355 @@ pub const DAY: Day = Day {
     name: "Report Repair",
     part_1: run_part1,
     part_2: Some(run_part2),
-    other: &[],
+    other: &[
+        ("Part 1 Strict", run_part1_strict),
+        ("Part 2 Strict", run_part2_strict),
+        ("Part 1 Count", run_part1_count),
+        ("Part 2 Count", run_part2_count),
+    ],
 };
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
-    let inputs: Vec<_> = input
-        .lines()
-        .map(str::trim)
-        .map(str::parse::<u32>)
-        .collect::<Result<_, _>>()
-        .map_err(UserError)?;
-
+    let inputs = parse_inputs(input).map_err(UserError)?;
     b.bench(|| part1(&inputs, 2020))
 }
 
 fn run_part2(input: &str, b: Bench) -> BenchResult {
+    let inputs = parse_inputs(input).map_err(UserError)?;
+    b.bench(|| part2(&inputs, 2020))
+}
+
+fn run_part1_strict(input: &str, b: Bench) -> BenchResult {
+    let inputs = parse_inputs(input).map_err(UserError)?;
+    b.bench(|| unique_product(&inputs, 2, 2020))
+}
+
+fn run_part2_strict(input: &str, b: Bench) -> BenchResult {
+    let inputs = parse_inputs(input).map_err(UserError)?;
+    b.bench(|| unique_product(&inputs, 3, 2020))
+}
+
+fn run_part1_count(input: &str, b: Bench) -> BenchResult {
+    let inputs = parse_inputs(input).map_err(UserError)?;
+    b.bench(|| count_solutions(&inputs, 2, 2020))
+}
+
+fn run_part2_count(input: &str, b: Bench) -> BenchResult {
+    let inputs = parse_inputs(input).map_err(UserError)?;
+    b.bench(|| count_solutions(&inputs, 3, 2020))
+}
+
+fn parse_inputs(input: &str) -> Result<SortedInputs, ParseIntError> {
     let inputs: Vec<_> = input
         .lines()
         .map(str::trim)
         .map(str::parse::<u32>)
-        .collect::<Result<_, _>>()
-        .map_err(UserError)?;
+        .collect::<Result<_, _>>()?;
 
-    b.bench(|| part2(&inputs, 2020))
+    Ok(SortedInputs::new(&inputs))
+}
+
+// The entries sorted by value, each with its index in the original input. Sorting is done
+// up front so that searching doesn't need to allocate.
+struct SortedInputs(Vec<(u32, usize)>);
+
+impl SortedInputs {
+    fn new(inputs: &[u32]) -> SortedInputs {
+        let mut sorted: Vec<_> = inputs.iter().copied().zip(0..).collect();
+        sorted.sort_unstable();
+        SortedInputs(sorted)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KSum {
+    indices: Vec<usize>,
+    values: Vec<u32>,
+}
+
+impl KSum {
+    fn product(&self) -> Result<u64> {
+        self.values
+            .iter()
+            .try_fold(1u64, |acc, &v| acc.checked_mul(v as u64))
+            .ok_or_else(|| eyre!("Product overflowed"))
+    }
+}
+
+// Finds sets of `k` entries, at distinct indices, which sum to `target`. Each result is
+// ordered by index.
+fn k_sum(inputs: &SortedInputs, k: usize, target: u32) -> KSumSolutions<'_> {
+    KSumSolutions::new(inputs, k, target)
+}
+
+// Yields every distinct combination of `k` values which sum to `target`, exactly once. A value
+// can appear in a combination as many times as it's in the input, and each combination uses
+// the lowest indices holding its values.
+//
+// All but the last two terms are chosen by backtracking, then the last two are found by
+// closing in from both ends of the remaining entries, so k terms take O(n^(k-1)) time.
+struct KSumSolutions<'a> {
+    sorted: &'a [(u32, usize)],
+    k: usize,
+    target: u64,
+    // Positions into `sorted` of the terms chosen so far, and their sum.
+    chosen: Vec<usize>,
+    chosen_sum: u64,
+    // Next position in `sorted` to try for the next chosen term.
+    cursor: usize,
+    // The low and high positions of the search for the last two terms, if one is running.
+    pair: Option<(usize, usize)>,
+    // Positions of the last terms of the current solution.
+    last: [usize; 2],
+    done: bool,
 }
 
-fn part1(inputs: &[u32], target: u32) -> Result<u32> {
-    for (i, lhs) in inputs.iter().enumerate() {
-        for rhs in &inputs[i..] {
-            if rhs + lhs == target {
-                return Ok(rhs * lhs);
+impl<'a> KSumSolutions<'a> {
+    fn new(inputs: &'a SortedInputs, k: usize, target: u32) -> Self {
+        Self {
+            sorted: &inputs.0,
+            k,
+            target: target as u64,
+            chosen: Vec::with_capacity(k.saturating_sub(2)),
+            chosen_sum: 0,
+            cursor: 0,
+            pair: None,
+            last: [0; 2],
+            done: false,
+        }
+    }
+
+    // The first position holding the same value as `pos`.
+    fn first_of(&self, pos: usize) -> usize {
+        let value = self.sorted[pos].0;
+        self.sorted.partition_point(|&(v, _)| v < value)
+    }
+
+    // The first position after `pos` holding a different value, so each value is only tried
+    // once at each depth.
+    fn next_value(&self, pos: usize) -> usize {
+        let value = self.sorted[pos].0;
+        pos + 1 + self.sorted[pos + 1..].partition_point(|&(v, _)| v == value)
+    }
+
+    // Gives up on the most recently chosen term, and moves on to the next value for it.
+    fn backtrack(&mut self) -> bool {
+        match self.chosen.pop() {
+            Some(pos) => {
+                self.chosen_sum -= self.sorted[pos].0 as u64;
+                self.cursor = self.next_value(pos);
+                true
             }
+            None => false,
         }
     }
 
-    Err(eyre!("Unable to find result"))
-}
+    // Moves on to the next solution, returning false once there are none left.
+    fn advance(&mut self) -> bool {
+        if self.done {
+            return false;
+        }
+
+        match self.k {
+            0 => {
+                self.done = true;
+                return self.target == 0;
+            }
+            1 => {
+                self.done = true;
+                self.last[0] = self
+                    .sorted
+                    .partition_point(|&(v, _)| (v as u64) < self.target);
+                let found = self.sorted.get(self.last[0]);
+                return matches!(found, Some(&(v, _)) if v as u64 == self.target);
+            }
+            _ => {}
+        }
 
-fn part2(inputs: &[u32], target: u32) -> Result<u32> {
-    for (i, lhs) in inputs.iter().enumerate() {
-        for (i, mhs) in inputs[i..].iter().enumerate() {
-            for rhs in &inputs[i..] {
-                if rhs + mhs + lhs == target {
-                    return Ok(rhs * mhs * lhs);
+        loop {
+            let remaining = self.target - self.chosen_sum;
+
+            if let Some((lo, hi)) = self.pair {
+                if lo >= hi {
+                    self.pair = None;
+                    if !self.backtrack() {
+                        self.done = true;
+                        return false;
+                    }
+                    continue;
+                }
+
+                let (low, high) = (self.sorted[lo].0, self.sorted[hi].0);
+                let lower_high = self.first_of(hi).saturating_sub(1);
+
+                match (low as u64 + high as u64).cmp(&remaining) {
+                    Ordering::Less => self.pair = Some((self.next_value(lo), hi)),
+                    Ordering::Greater => self.pair = Some((lo, lower_high)),
+                    Ordering::Equal => {
+                        let second = if low == high {
+                            lo + 1
+                        } else {
+                            self.first_of(hi)
+                        };
+                        self.last = [lo, second];
+                        self.pair = Some((self.next_value(lo), lower_high));
+                        return true;
+                    }
+                }
+
+                continue;
+            }
+
+            if self.chosen.len() + 2 == self.k {
+                self.pair = Some((self.cursor, self.sorted.len().saturating_sub(1)));
+                continue;
+            }
+
+            // Every later entry is at least as big as this one, so if this one's too big
+            // nothing else at this depth can fit either.
+            let terms_left = (self.k - self.chosen.len()) as u64;
+            let candidate = self
+                .sorted
+                .get(self.cursor)
+                .map(|&(value, _)| value as u64)
+                .filter(|&value| value * terms_left <= remaining);
+
+            match candidate {
+                Some(value) => {
+                    self.chosen.push(self.cursor);
+                    self.chosen_sum += value;
+                    self.cursor += 1;
+                }
+                None if self.backtrack() => {}
+                None => {
+                    self.done = true;
+                    return false;
                 }
             }
         }
     }
 
-    Err(eyre!("Unable to find result"))
+    // The (value, index) of each term in the current solution.
+    fn terms(&self) -> impl Iterator<Item = (u32, usize)> + '_ {
+        let last = &self.last[..self.k.min(2)];
+        self.chosen
+            .iter()
+            .chain(last)
+            .map(move |&pos| self.sorted[pos])
+    }
+
+    fn current_product(&self) -> Result<u64> {
+        self.terms()
+            .try_fold(1u64, |acc, (v, _)| acc.checked_mul(v as u64))
+            .ok_or_else(|| eyre!("Product overflowed"))
+    }
+
+    fn solution(&self) -> KSum {
+        let mut terms: Vec<_> = self.terms().collect();
+        terms.sort_unstable_by_key(|&(_, idx)| idx);
+
+        KSum {
+            indices: terms.iter().map(|&(_, idx)| idx).collect(),
+            values: terms.iter().map(|&(value, _)| value).collect(),
+        }
+    }
+}
+
+impl Iterator for KSumSolutions<'_> {
+    type Item = KSum;
+
+    fn next(&mut self) -> Option<KSum> {
+        if self.advance() {
+            Some(self.solution())
+        } else {
+            None
+        }
+    }
+}
+
+// The product of the first solution found, without allocating.
+fn first_product(inputs: &SortedInputs, k: usize, target: u32) -> Result<u64> {
+    let mut solutions = k_sum(inputs, k, target);
+    if !solutions.advance() {
+        return Err(eyre!("Unable to find result"));
+    }
+
+    solutions.current_product()
+}
+
+// Like `k_sum`, but fails if the solutions don't all have the same product.
+fn unique_product(inputs: &SortedInputs, k: usize, target: u32) -> Result<u64> {
+    let mut solutions = k_sum(inputs, k, target);
+    let first = solutions
+        .next()
+        .ok_or_else(|| eyre!("Unable to find result"))?;
+    let product = first.product()?;
+
+    for other in solutions {
+        let other_product = other.product()?;
+        if other_product != product {
+            return Err(eyre!(
+                "Ambiguous result: {:?} gives {}, but {:?} gives {}",
+                first.values,
+                product,
+                other.values,
+                other_product
+            ));
+        }
+    }
+
+    Ok(product)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct SolutionCount {
+    solutions: usize,
+    // Whether the solutions don't all have the same product.
+    ambiguous: bool,
+}
+
+impl Display for SolutionCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let plural = if self.solutions == 1 { "" } else { "s" };
+        let ambiguous = if self.ambiguous {
+            "ambiguous"
+        } else {
+            "unambiguous"
+        };
+        write!(f, "{} solution{}, {}", self.solutions, plural, ambiguous)
+    }
+}
+
+fn count_solutions(inputs: &SortedInputs, k: usize, target: u32) -> Result<SolutionCount> {
+    let mut solutions = k_sum(inputs, k, target);
+    let mut count = 0;
+    let mut products = None;
+    let mut ambiguous = false;
+
+    while solutions.advance() {
+        let product = solutions.current_product()?;
+        count += 1;
+        ambiguous |= *products.get_or_insert(product) != product;
+    }
+
+    Ok(SolutionCount {
+        solutions: count,
+        ambiguous,
+    })
+}
+
+fn part1(inputs: &SortedInputs, target: u32) -> Result<u64> {
+    first_product(inputs, 2, target)
+}
+
+fn part2(inputs: &SortedInputs, target: u32) -> Result<u64> {
+    first_product(inputs, 3, target)
 }
 
 #[cfg(test)]
 mod tests_2001 {
-    use std::num::ParseIntError;
-
     use aoc_lib::Example;
+    use itertools::Itertools;
 
     use super::*;
 
@@ -69,12 +368,7 @@ mod tests_2001 {
     fn part1_example() {
         let input = aoc_lib::input(1).example(Example::Part1, 1).open().unwrap();
 
-        let inputs: Vec<_> = input
-            .lines()
-            .map(str::trim)
-            .map(str::parse)
-            .collect::<Result<_, ParseIntError>>()
-            .unwrap();
+        let inputs = parse_inputs(&input).unwrap();
 
         assert_eq!(514579, part1(&inputs, 2020).unwrap());
     }
@@ -83,13 +377,187 @@ mod tests_2001 {
     fn part2_example() {
         let input = aoc_lib::input(1).example(Example::Part1, 1).open().unwrap();
 
-        let inputs: Vec<_> = input
-            .lines()
-            .map(str::trim)
-            .map(str::parse)
-            .collect::<Result<_, ParseIntError>>()
-            .unwrap();
+        let inputs = parse_inputs(&input).unwrap();
 
         assert_eq!(241861950, part2(&inputs, 2020).unwrap());
     }
+
+    #[test]
+    fn k_sum_test() {
+        let input = aoc_lib::input(1).example(Example::Part1, 1).open().unwrap();
+
+        let inputs = parse_inputs(&input).unwrap();
+
+        let expected = KSum {
+            indices: vec![0, 3],
+            values: vec![1721, 299],
+        };
+        assert_eq!(k_sum(&inputs, 2, 2020).next(), Some(expected));
+
+        let expected = KSum {
+            indices: vec![1, 2, 4],
+            values: vec![979, 366, 675],
+        };
+        assert_eq!(k_sum(&inputs, 3, 2020).next(), Some(expected));
+
+        let expected = KSum {
+            indices: vec![3],
+            values: vec![299],
+        };
+        assert_eq!(k_sum(&inputs, 1, 299).next(), Some(expected));
+
+        assert_eq!(k_sum(&inputs, 4, 2020).next(), None);
+        assert_eq!(k_sum(&inputs, 7, 5496).next(), None);
+    }
+
+    #[test]
+    fn k_sum_distinct_test() {
+        // The same entry can't be used twice, even if it's half the target.
+        assert_eq!(k_sum(&SortedInputs::new(&[1010, 5]), 2, 2020).next(), None);
+
+        let expected = KSum {
+            indices: vec![0, 2],
+            values: vec![1010, 1010],
+        };
+        assert_eq!(
+            k_sum(&SortedInputs::new(&[1010, 5, 1010]), 2, 2020).next(),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn all_solutions_test() {
+        let inputs = SortedInputs::new(&[1010, 5, 1010, 2015, 1010]);
+
+        let actual: Vec<_> = KSumSolutions::new(&inputs, 2, 2020)
+            .map(|s| s.indices)
+            .sorted()
+            .collect();
+        let expected = [vec![0, 2], vec![1, 3]];
+        assert_eq!(actual, expected);
+
+        let actual: Vec<_> = KSumSolutions::new(&inputs, 3, 2025)
+            .map(|s| s.indices)
+            .sorted()
+            .collect();
+        let expected = [vec![0, 1, 2]];
+        assert_eq!(actual, expected);
+
+        assert_eq!(KSumSolutions::new(&inputs, 0, 0).count(), 1);
+        assert_eq!(KSumSolutions::new(&inputs, 6, 6050).count(), 0);
+    }
+
+    #[test]
+    fn ambiguity_test() {
+        let inputs = SortedInputs::new(&[1010, 1010, 1010]);
+        assert_eq!(unique_product(&inputs, 2, 2020).unwrap(), 1020100);
+
+        let inputs = SortedInputs::new(&[1, 2019, 20, 2000]);
+        assert_eq!(part1(&inputs, 2020).unwrap(), 2019);
+        assert!(unique_product(&inputs, 2, 2020).is_err());
+    }
+
+    #[test]
+    fn duplicate_values_test() {
+        let inputs = SortedInputs::new(&[1010, 1010, 1010]);
+        let actual: Vec<_> = KSumSolutions::new(&inputs, 2, 2020).collect();
+        let expected = [KSum {
+            indices: vec![0, 1],
+            values: vec![1010, 1010],
+        }];
+        assert_eq!(actual, expected);
+
+        // Each combination of values once, whichever order the duplicates come in.
+        let inputs = SortedInputs::new(&[3, 1, 2, 1, 3, 2, 1]);
+        let actual: Vec<_> = KSumSolutions::new(&inputs, 3, 5)
+            .map(|s| s.values.into_iter().sorted().collect::<Vec<_>>())
+            .sorted()
+            .collect();
+        let expected = [vec![1, 1, 3], vec![1, 2, 2]];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn count_solutions_test() {
+        let count = |inputs: &[u32], k, target| {
+            count_solutions(&SortedInputs::new(inputs), k, target).unwrap()
+        };
+
+        let input = aoc_lib::input(1).example(Example::Part1, 1).open().unwrap();
+        let inputs = parse_inputs(&input).unwrap();
+        let unique = SolutionCount {
+            solutions: 1,
+            ambiguous: false,
+        };
+        assert_eq!(count_solutions(&inputs, 2, 2020).unwrap(), unique);
+        assert_eq!(count_solutions(&inputs, 3, 2020).unwrap(), unique);
+
+        assert_eq!(count(&[1010, 1010, 1010], 2, 2020), unique);
+        assert_eq!(count(&[1010, 5, 1010, 2015, 1010, 5], 2, 2020).solutions, 2);
+
+        let expected = SolutionCount {
+            solutions: 2,
+            ambiguous: true,
+        };
+        assert_eq!(count(&[1, 2019, 20, 2000, 2019], 2, 2020), expected);
+
+        // Different values, but the same product.
+        let expected = SolutionCount {
+            solutions: 2,
+            ambiguous: false,
+        };
+        assert_eq!(count(&[2, 6, 3, 6, 3, 8], 3, 14), expected);
+
+        let none = SolutionCount {
+            solutions: 0,
+            ambiguous: false,
+        };
+        assert_eq!(count(&[1010], 2, 2020), none);
+
+        assert_eq!(unique.to_string(), "1 solution, unambiguous");
+        assert_eq!(expected.to_string(), "2 solutions, unambiguous");
+    }
+
+    #[test]
+    fn brute_force_test() {
+        // Plenty of small, repeated values, so there are lots of solutions to find.
+        let inputs: Vec<u32> = (0..40u32).map(|i| i * 7919 % 23).collect();
+        let sorted = SortedInputs::new(&inputs);
+
+        for k in 0..=4 {
+            let combinations: Vec<Vec<u32>> = (0..inputs.len())
+                .combinations(k)
+                .map(|c| c.iter().map(|&i| inputs[i]).sorted().collect())
+                .sorted()
+                .dedup()
+                .collect();
+
+            for target in 0..=60 {
+                let expected: Vec<_> = combinations
+                    .iter()
+                    .filter(|values| values.iter().sum::<u32>() == target)
+                    .collect();
+
+                let solutions: Vec<_> = k_sum(&sorted, k, target).collect();
+                for solution in &solutions {
+                    let values: Vec<_> = solution.indices.iter().map(|&i| inputs[i]).collect();
+                    assert_eq!(values, solution.values);
+                    assert!(solution.indices.windows(2).all(|w| w[0] < w[1]));
+                }
+
+                let actual: Vec<_> = solutions
+                    .into_iter()
+                    .map(|s| s.values.into_iter().sorted().collect::<Vec<_>>())
+                    .sorted()
+                    .collect();
+                assert_eq!(
+                    actual.iter().collect::<Vec<_>>(),
+                    expected,
+                    "k = {}, target = {}",
+                    k,
+                    target
+                );
+            }
+        }
+    }
 }
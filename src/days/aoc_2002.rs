@@ -1,19 +1,37 @@
-use aoc_lib::{parsers::unsigned_number, Bench, BenchResult, Day, ParseResult, UserError};
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+    ops::RangeInclusive,
+};
+
+use aoc_lib::{parsers::unsigned_number, Bench, BenchResult, Day, NoError, ParseResult, UserError};
 use color_eyre::{
     eyre::{eyre, Result},
     Report,
 };
+use itertools::Itertools;
 use nom::{
     bytes::complete::{tag, take_till1, take_while1},
     sequence::tuple,
 };
+use unicode_segmentation::UnicodeSegmentation;
 
 pub const DAY: Day = Day {
     day: 2,
     name: "Passward Philosophy",
     part_1: run_part1,
     part_2: Some(run_part2),
-    other: &[("Parse", run_parse)],
+    other: &[
+        ("Parse", run_parse),
+        ("Both Policies", run_both_policies),
+        ("Either Policy", run_either_policy),
+        ("Part 2 Graphemes", run_part2_graphemes),
+        ("Leading Positions", run_leading_positions),
+        ("Strict", run_strict),
+        ("Audit", run_audit),
+        ("Audit Table", run_audit_table),
+        ("Audit CSV", run_audit_csv),
+    ],
 };
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
@@ -38,6 +56,131 @@ fn run_part2(input: &str, b: Bench) -> BenchResult {
     b.bench(|| part2(&inputs))
 }
 
+fn run_part2_graphemes(input: &str, b: Bench) -> BenchResult {
+    let inputs: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Password::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+
+    b.bench(|| count_valid(&inputs, &PositionXor(PositionUnit::Graphemes)))
+}
+
+fn run_both_policies(input: &str, b: Bench) -> BenchResult {
+    let inputs: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Password::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+    let policy = All(vec![
+        Box::new(CountInRange),
+        Box::new(PositionXor(PositionUnit::Chars)),
+    ]);
+
+    b.bench(|| count_valid(&inputs, &policy))
+}
+
+fn run_either_policy(input: &str, b: Bench) -> BenchResult {
+    let inputs: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Password::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+    let policy = Any(vec![
+        Box::new(CountInRange),
+        Box::new(PositionXor(PositionUnit::Chars)),
+    ]);
+
+    b.bench(|| count_valid(&inputs, &policy))
+}
+
+fn run_leading_positions(input: &str, b: Bench) -> BenchResult {
+    let inputs: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Password::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+    let policy = AtLeastPositions {
+        positions: vec![1, 2, 3],
+        count: 2,
+        unit: PositionUnit::Chars,
+    };
+
+    b.bench(|| count_valid(&inputs, &policy))
+}
+
+fn run_strict(input: &str, b: Bench) -> BenchResult {
+    let inputs: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Password::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+    let policy = All(vec![
+        Box::new(CountInRange),
+        Box::new(LengthInRange(8..=20)),
+        Box::new(ForbiddenSubstring("password")),
+    ]);
+
+    b.bench(|| count_valid(&inputs, &policy))
+}
+
+fn run_audit(input: &str, b: Bench) -> BenchResult {
+    let inputs: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Password::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+
+    b.bench(|| {
+        let report = AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars));
+        Ok::<_, NoError>(report.summary())
+    })
+}
+
+// These print the full report before benchmarking, so the answer stays on one line.
+// The benchmark itself is the same as `run_audit`.
+fn run_audit_table(input: &str, b: Bench) -> BenchResult {
+    let inputs: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Password::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+    print!(
+        "{}",
+        AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars))
+    );
+
+    b.bench(|| {
+        let report = AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars));
+        Ok::<_, NoError>(report.summary())
+    })
+}
+
+fn run_audit_csv(input: &str, b: Bench) -> BenchResult {
+    let inputs: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Password::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+    print!(
+        "{}",
+        AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars)).to_csv()
+    );
+
+    b.bench(|| {
+        let report = AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars));
+        Ok::<_, NoError>(report.summary())
+    })
+}
+
 fn run_parse(input: &str, b: Bench) -> BenchResult {
     b.bench(|| {
         let data: Vec<_> = input
@@ -78,24 +221,466 @@ impl<'a> Password<'a> {
         })
     }
 
-    fn part1_is_valid(&self) -> bool {
-        (self.min..=self.max).contains(&self.pswd.matches(self.check).count())
+    // Positions are 1-based. Anything outside the password gives `None`.
+    fn check_at(&self, position: usize, unit: PositionUnit) -> Option<bool> {
+        let start = position
+            .checked_sub(1)
+            .and_then(|p| unit.offset(self.pswd, p))
+            .filter(|&start| start < self.pswd.len())?;
+        let rest = &self.pswd[start..];
+
+        let end = unit.offset(rest, unit.count(self.check));
+
+        Some(matches!(end, Some(end) if rest[..end] == *self.check))
+    }
+
+    fn length(&self, unit: PositionUnit) -> usize {
+        unit.count(self.pswd)
+    }
+}
+
+// How positions and lengths within a password are counted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum PositionUnit {
+    Chars,
+    Graphemes,
+}
+
+impl PositionUnit {
+    // Byte offset of the start of the `n`th unit, or the string length if `n` is one past the end.
+    fn offset(self, s: &str, n: usize) -> Option<usize> {
+        match self {
+            PositionUnit::Chars => s.char_indices().map(|(i, _)| i).chain(Some(s.len())).nth(n),
+            PositionUnit::Graphemes => s
+                .grapheme_indices(true)
+                .map(|(i, _)| i)
+                .chain(Some(s.len()))
+                .nth(n),
+        }
+    }
+
+    fn count(self, s: &str) -> usize {
+        match self {
+            PositionUnit::Chars => s.chars().count(),
+            PositionUnit::Graphemes => s.graphemes(true).count(),
+        }
+    }
+}
+
+trait PasswordPolicy {
+    fn is_valid(&self, password: &Password) -> bool;
+
+    // Describes what the policy requires of this password, and what it found, for use in
+    // audit reports.
+    fn describe(&self, password: &Password) -> String;
+    fn explain(&self, password: &Password) -> Reason;
+}
+
+// The check string must occur between min and max times.
+struct CountInRange;
+
+impl PasswordPolicy for CountInRange {
+    fn is_valid(&self, password: &Password) -> bool {
+        (password.min..=password.max).contains(&password.pswd.matches(password.check).count())
+    }
+
+    fn describe(&self, password: &Password) -> String {
+        format!(
+            "'{}' {}-{} times",
+            password.check, password.min, password.max
+        )
+    }
+
+    fn explain(&self, password: &Password) -> Reason {
+        Reason::Occurrences {
+            check: password.check.to_owned(),
+            found: password.pswd.matches(password.check).count(),
+            min: password.min,
+            max: password.max,
+        }
+    }
+}
+
+// The check string must occur at exactly one of positions min and max. Both positions must
+// be inside the password.
+struct PositionXor(PositionUnit);
+
+impl PasswordPolicy for PositionXor {
+    fn is_valid(&self, password: &Password) -> bool {
+        match (
+            password.check_at(password.min, self.0),
+            password.check_at(password.max, self.0),
+        ) {
+            (Some(first), Some(second)) => first ^ second,
+            _ => false,
+        }
+    }
+
+    fn describe(&self, password: &Password) -> String {
+        format!(
+            "'{}' at {} xor {}",
+            password.check, password.min, password.max
+        )
+    }
+
+    fn explain(&self, password: &Password) -> Reason {
+        let first = password.check_at(password.min, self.0);
+        let second = password.check_at(password.max, self.0);
+
+        match (first, second) {
+            (Some(first), Some(second)) => Reason::ExactlyOnePosition {
+                check: password.check.to_owned(),
+                positions: [password.min, password.max],
+                matched: [first, second],
+            },
+            (None, _) => Reason::OutOfRange {
+                position: password.min,
+                length: password.length(self.0),
+            },
+            (_, None) => Reason::OutOfRange {
+                position: password.max,
+                length: password.length(self.0),
+            },
+        }
+    }
+}
+
+// The check string must occur at no fewer than `count` of the given positions. Positions
+// outside the password never match.
+struct AtLeastPositions {
+    positions: Vec<usize>,
+    count: usize,
+    unit: PositionUnit,
+}
+
+impl PasswordPolicy for AtLeastPositions {
+    fn is_valid(&self, password: &Password) -> bool {
+        self.positions
+            .iter()
+            .filter(|&&pos| password.check_at(pos, self.unit) == Some(true))
+            .take(self.count)
+            .count()
+            == self.count
+    }
+
+    fn describe(&self, password: &Password) -> String {
+        format!(
+            "'{}' at {} of {:?}",
+            password.check, self.count, self.positions
+        )
+    }
+
+    fn explain(&self, password: &Password) -> Reason {
+        Reason::AtLeastPositions {
+            check: password.check.to_owned(),
+            matched: self
+                .positions
+                .iter()
+                .copied()
+                .filter(|&pos| password.check_at(pos, self.unit) == Some(true))
+                .collect(),
+            positions: self.positions.clone(),
+            required: self.count,
+        }
+    }
+}
+
+struct ForbiddenSubstring<'a>(&'a str);
+
+impl PasswordPolicy for ForbiddenSubstring<'_> {
+    fn is_valid(&self, password: &Password) -> bool {
+        !password.pswd.contains(self.0)
+    }
+
+    fn describe(&self, _: &Password) -> String {
+        format!("no '{}'", self.0)
+    }
+
+    fn explain(&self, password: &Password) -> Reason {
+        Reason::Forbidden {
+            substring: self.0.to_owned(),
+            found: password.pswd.contains(self.0),
+        }
+    }
+}
+
+struct LengthInRange(RangeInclusive<usize>);
+
+impl PasswordPolicy for LengthInRange {
+    fn is_valid(&self, password: &Password) -> bool {
+        self.0.contains(&password.length(PositionUnit::Chars))
+    }
+
+    fn describe(&self, _: &Password) -> String {
+        format!("length {}-{}", self.0.start(), self.0.end())
+    }
+
+    fn explain(&self, password: &Password) -> Reason {
+        Reason::Length {
+            length: password.length(PositionUnit::Chars),
+            min: *self.0.start(),
+            max: *self.0.end(),
+        }
+    }
+}
+
+struct All(Vec<Box<dyn PasswordPolicy>>);
+
+impl PasswordPolicy for All {
+    fn is_valid(&self, password: &Password) -> bool {
+        self.0.iter().all(|p| p.is_valid(password))
+    }
+
+    fn describe(&self, password: &Password) -> String {
+        format!(
+            "({})",
+            self.0.iter().map(|p| p.describe(password)).join(" and ")
+        )
+    }
+
+    fn explain(&self, password: &Password) -> Reason {
+        Reason::Combined(self.0.iter().map(|p| p.explain(password)).collect())
+    }
+}
+
+struct Any(Vec<Box<dyn PasswordPolicy>>);
+
+impl PasswordPolicy for Any {
+    fn is_valid(&self, password: &Password) -> bool {
+        self.0.iter().any(|p| p.is_valid(password))
+    }
+
+    fn describe(&self, password: &Password) -> String {
+        format!(
+            "({})",
+            self.0.iter().map(|p| p.describe(password)).join(" or ")
+        )
+    }
+
+    fn explain(&self, password: &Password) -> Reason {
+        Reason::Combined(self.0.iter().map(|p| p.explain(password)).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Reason {
+    Occurrences {
+        check: String,
+        found: usize,
+        min: usize,
+        max: usize,
+    },
+    ExactlyOnePosition {
+        check: String,
+        positions: [usize; 2],
+        matched: [bool; 2],
+    },
+    AtLeastPositions {
+        check: String,
+        positions: Vec<usize>,
+        matched: Vec<usize>,
+        required: usize,
+    },
+    Forbidden {
+        substring: String,
+        found: bool,
+    },
+    Length {
+        length: usize,
+        min: usize,
+        max: usize,
+    },
+    OutOfRange {
+        position: usize,
+        length: usize,
+    },
+    Combined(Vec<Reason>),
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Occurrences {
+                check,
+                found,
+                min,
+                max,
+            } => write!(
+                f,
+                "found {} occurrences of '{}', allowed {}-{}",
+                found, check, min, max
+            ),
+            Reason::ExactlyOnePosition {
+                check,
+                positions: [first, second],
+                matched,
+            } => match matched {
+                [true, true] => write!(
+                    f,
+                    "both positions {} and {} match '{}'",
+                    first, second, check
+                ),
+                [false, false] => write!(
+                    f,
+                    "neither position {} nor {} matches '{}'",
+                    first, second, check
+                ),
+                [true, false] => write!(f, "only position {} matches '{}'", first, check),
+                [false, true] => write!(f, "only position {} matches '{}'", second, check),
+            },
+            Reason::AtLeastPositions {
+                check,
+                positions,
+                matched,
+                required,
+            } => write!(
+                f,
+                "'{}' found at positions {:?} of {:?}, needed at least {}",
+                check, matched, positions, required
+            ),
+            Reason::Forbidden {
+                substring,
+                found: true,
+            } => write!(f, "contains forbidden '{}'", substring),
+            Reason::Forbidden {
+                substring,
+                found: false,
+            } => write!(f, "does not contain '{}'", substring),
+            Reason::Length { length, min, max } => {
+                write!(f, "length {}, allowed {}-{}", length, min, max)
+            }
+            Reason::OutOfRange { position, length } => write!(
+                f,
+                "position {} is outside the password (length {})",
+                position, length
+            ),
+            Reason::Combined(reasons) => {
+                for (i, reason) in reasons.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{}", reason)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AuditRecord<'a> {
+    line: usize,
+    password: &'a Password<'a>,
+    policy: String,
+    valid: bool,
+    reason: Reason,
+}
+
+struct AuditReport<'a> {
+    records: Vec<AuditRecord<'a>>,
+}
+
+impl<'a> AuditReport<'a> {
+    const HEADERS: [&'static str; 5] = ["Line", "Policy", "Password", "Result", "Reason"];
+
+    fn new(inputs: &'a [Password<'a>], policy: &dyn PasswordPolicy) -> AuditReport<'a> {
+        let records = inputs
+            .iter()
+            .zip(1..)
+            .map(|(password, line)| AuditRecord {
+                line,
+                password,
+                policy: policy.describe(password),
+                valid: policy.is_valid(password),
+                reason: policy.explain(password),
+            })
+            .collect();
+
+        AuditReport { records }
+    }
+
+    fn failures(&self) -> usize {
+        self.records.iter().filter(|r| !r.valid).count()
+    }
+
+    fn summary(&self) -> String {
+        format!("{} of {} failed", self.failures(), self.records.len())
+    }
+
+    fn rows(&self) -> impl Iterator<Item = [String; 5]> + '_ {
+        self.records.iter().map(|r| {
+            [
+                r.line.to_string(),
+                r.policy.clone(),
+                r.password.pswd.to_owned(),
+                if r.valid { "pass" } else { "fail" }.to_owned(),
+                r.reason.to_string(),
+            ]
+        })
+    }
+
+    fn to_csv(&self) -> String {
+        fn escape(field: &str) -> Cow<'_, str> {
+            if field.contains(&[',', '"', '\n', '\r'][..]) {
+                format!("\"{}\"", field.replace('"', "\"\"")).into()
+            } else {
+                field.into()
+            }
+        }
+
+        let mut csv = Self::HEADERS.join(",").to_lowercase();
+        csv.push('\n');
+
+        for row in self.rows() {
+            csv.push_str(&row.iter().map(|f| escape(f)).join(","));
+            csv.push('\n');
+        }
+
+        csv
     }
+}
 
-    fn part2_is_valid(&self) -> bool {
-        let first = self.pswd[self.min - 1..].starts_with(self.check);
-        let second = self.pswd[self.max - 1..].starts_with(self.check);
+// Renders as a plain-text table.
+impl Display for AuditReport<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rows: Vec<_> = self.rows().collect();
+
+        let mut widths = Self::HEADERS.map(str::len);
+        for row in &rows {
+            for (width, field) in widths.iter_mut().zip(row) {
+                *width = (*width).max(field.chars().count());
+            }
+        }
 
-        first ^ second
+        let write_row = |f: &mut Formatter<'_>, row: &[&str]| {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(field, &width)| format!("{:<width$}", field, width = width))
+                .join(" | ");
+            writeln!(f, "{}", line.trim_end())
+        };
+
+        write_row(f, &Self::HEADERS)?;
+        writeln!(f, "{}", widths.iter().map(|&w| "-".repeat(w)).join("-|-"))?;
+        for row in &rows {
+            write_row(f, &row.iter().map(String::as_str).collect::<Vec<_>>())?;
+        }
+
+        Ok(())
     }
 }
 
+fn count_valid(inputs: &[Password], policy: &dyn PasswordPolicy) -> Result<usize> {
+    Ok(inputs.iter().filter(|p| policy.is_valid(p)).count())
+}
+
 fn part1(inputs: &[Password]) -> Result<usize> {
-    Ok(inputs.iter().filter(|p| p.part1_is_valid()).count())
+    count_valid(inputs, &CountInRange)
 }
 
 fn part2(inputs: &[Password]) -> Result<usize> {
-    Ok(inputs.iter().filter(|p| p.part2_is_valid()).count())
+    count_valid(inputs, &PositionXor(PositionUnit::Chars))
 }
 
 #[cfg(test)]
@@ -172,7 +757,7 @@ mod tests_2002 {
         ];
 
         for (pswd, expected) in &tests {
-            assert_eq!(pswd.part1_is_valid(), *expected);
+            assert_eq!(CountInRange.is_valid(pswd), *expected);
         }
     }
 
@@ -209,7 +794,280 @@ mod tests_2002 {
         ];
 
         for (pswd, expected) in &tests {
-            assert_eq!(pswd.part2_is_valid(), *expected);
+            assert_eq!(PositionXor(PositionUnit::Chars).is_valid(pswd), *expected);
+        }
+    }
+
+    #[test]
+    fn extra_policies_test() {
+        let pswd = Password {
+            min: 1,
+            max: 3,
+            check: "a",
+            pswd: "abade",
+        };
+
+        let tests: [(Box<dyn PasswordPolicy>, bool); 9] = [
+            (
+                Box::new(AtLeastPositions {
+                    positions: vec![1, 3, 5],
+                    count: 2,
+                    unit: PositionUnit::Chars,
+                }),
+                true,
+            ),
+            (
+                Box::new(AtLeastPositions {
+                    positions: vec![1, 2, 5, 50],
+                    count: 2,
+                    unit: PositionUnit::Chars,
+                }),
+                false,
+            ),
+            (Box::new(ForbiddenSubstring("bad")), false),
+            (Box::new(ForbiddenSubstring("dab")), true),
+            (Box::new(LengthInRange(1..=5)), true),
+            (Box::new(LengthInRange(6..=10)), false),
+            (
+                Box::new(All(vec![
+                    Box::new(CountInRange),
+                    Box::new(LengthInRange(1..=5)),
+                ])),
+                true,
+            ),
+            (
+                Box::new(All(vec![
+                    Box::new(CountInRange),
+                    Box::new(PositionXor(PositionUnit::Chars)),
+                ])),
+                false,
+            ),
+            (
+                Box::new(Any(vec![
+                    Box::new(PositionXor(PositionUnit::Chars)),
+                    Box::new(CountInRange),
+                ])),
+                true,
+            ),
+        ];
+
+        for (i, (policy, expected)) in tests.iter().enumerate() {
+            assert_eq!(policy.is_valid(&pswd), *expected, "{}", i);
         }
     }
+
+    #[test]
+    fn combined_example() {
+        let input = aoc_lib::input(2).example(Example::Part1, 1).open().unwrap();
+        let inputs: Vec<_> = input
+            .lines()
+            .map(str::trim)
+            .map(Password::parse)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        let both = All(vec![
+            Box::new(CountInRange),
+            Box::new(PositionXor(PositionUnit::Chars)),
+        ]);
+        let either = Any(vec![
+            Box::new(CountInRange),
+            Box::new(PositionXor(PositionUnit::Chars)),
+        ]);
+
+        assert_eq!(count_valid(&inputs, &both).unwrap(), 1);
+        assert_eq!(count_valid(&inputs, &either).unwrap(), 2);
+    }
+
+    #[test]
+    fn audit_test() {
+        let input = aoc_lib::input(2).example(Example::Part1, 1).open().unwrap();
+        let inputs: Vec<_> = input
+            .lines()
+            .map(str::trim)
+            .map(Password::parse)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        let report = AuditReport::new(&inputs, &CountInRange);
+        let expected = [
+            (true, "found 1 occurrences of 'a', allowed 1-3"),
+            (false, "found 0 occurrences of 'b', allowed 1-3"),
+            (true, "found 9 occurrences of 'c', allowed 2-9"),
+        ];
+        for (record, (valid, reason)) in report.records.iter().zip(&expected) {
+            assert_eq!(record.valid, *valid, "{}", record.line);
+            assert_eq!(record.reason.to_string(), *reason, "{}", record.line);
+        }
+
+        let report = AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars));
+        let expected = [
+            (true, "only position 1 matches 'a'"),
+            (false, "neither position 1 nor 3 matches 'b'"),
+            (false, "both positions 2 and 9 match 'c'"),
+        ];
+        for (record, (valid, reason)) in report.records.iter().zip(&expected) {
+            assert_eq!(record.valid, *valid, "{}", record.line);
+            assert_eq!(record.reason.to_string(), *reason, "{}", record.line);
+        }
+        assert_eq!(report.failures(), 2);
+    }
+
+    #[test]
+    fn audit_output_test() {
+        let input = aoc_lib::input(2).example(Example::Part1, 1).open().unwrap();
+        let inputs: Vec<_> = input
+            .lines()
+            .map(str::trim)
+            .map(Password::parse)
+            .collect::<Result<_>>()
+            .unwrap();
+        let report = AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars));
+
+        let expected_table = "\
+Line | Policy         | Password  | Result | Reason
+-----|----------------|-----------|--------|-------------------------------------
+1    | 'a' at 1 xor 3 | abcde     | pass   | only position 1 matches 'a'
+2    | 'b' at 1 xor 3 | cdefg     | fail   | neither position 1 nor 3 matches 'b'
+3    | 'c' at 2 xor 9 | ccccccccc | fail   | both positions 2 and 9 match 'c'
+";
+        assert_eq!(report.to_string(), expected_table);
+
+        let expected_csv = "\
+line,policy,password,result,reason
+1,'a' at 1 xor 3,abcde,pass,only position 1 matches 'a'
+2,'b' at 1 xor 3,cdefg,fail,neither position 1 nor 3 matches 'b'
+3,'c' at 2 xor 9,ccccccccc,fail,both positions 2 and 9 match 'c'
+";
+        assert_eq!(report.to_csv(), expected_csv);
+    }
+
+    #[test]
+    fn audit_combined_policy_test() {
+        let inputs = [
+            Password::parse("1-3 a: abade").unwrap(),
+            Password::parse("2-4 b: bbb").unwrap(),
+        ];
+        let policy = All(vec![
+            Box::new(AtLeastPositions {
+                positions: vec![1, 3, 5],
+                count: 2,
+                unit: PositionUnit::Chars,
+            }),
+            Box::new(Any(vec![
+                Box::new(LengthInRange(4..=10)),
+                Box::new(ForbiddenSubstring("bad")),
+            ])),
+        ]);
+        let report = AuditReport::new(&inputs, &policy);
+
+        let expected_csv = "\
+line,policy,password,result,reason
+1,\"('a' at 2 of [1, 3, 5] and (length 4-10 or no 'bad'))\",abade,pass,\"'a' found at positions [1, 3] of [1, 3, 5], needed at least 2; length 5, allowed 4-10; contains forbidden 'bad'\"
+2,\"('b' at 2 of [1, 3, 5] and (length 4-10 or no 'bad'))\",bbb,pass,\"'b' found at positions [1, 3] of [1, 3, 5], needed at least 2; length 3, allowed 4-10; does not contain 'bad'\"
+";
+        assert_eq!(report.to_csv(), expected_csv);
+        assert_eq!(report.failures(), 0);
+
+        let report = AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars));
+        let policies: Vec<_> = report.records.iter().map(|r| r.policy.as_str()).collect();
+        assert_eq!(policies, ["'a' at 1 xor 3", "'b' at 2 xor 4"]);
+    }
+
+    #[test]
+    fn audit_csv_escape_test() {
+        let inputs = [Password {
+            min: 1,
+            max: 2,
+            check: ",",
+            pswd: "a,\"b",
+        }];
+        let report = AuditReport::new(&inputs, &PositionXor(PositionUnit::Chars));
+
+        let expected = "\
+line,policy,password,result,reason
+1,\"',' at 1 xor 2\",\"a,\"\"b\",pass,\"only position 2 matches ','\"
+";
+        assert_eq!(report.to_csv(), expected);
+    }
+
+    #[test]
+    fn unicode_parse_test() {
+        let expected = Password {
+            min: 1,
+            max: 3,
+            check: "é",
+            pswd: "éaé",
+        };
+
+        assert_eq!(Password::parse("1-3 é: éaé").unwrap(), expected);
+    }
+
+    #[test]
+    fn unicode_positions_test() {
+        let chars = PositionXor(PositionUnit::Chars);
+        let graphemes = PositionXor(PositionUnit::Graphemes);
+
+        // (min, max, check, password, valid by chars, valid by graphemes)
+        let tests = [
+            (1, 3, "é", "éaé", false, false),
+            // Position 3 is in the middle of `é` when counting bytes.
+            (1, 3, "b", "aéb", true, true),
+            (2, 3, "日", "日本日", true, true),
+            // `e\u{301}` is one grapheme, but two chars.
+            (1, 2, "e", "ae\u{301}e", true, false),
+            (2, 3, "e", "ae\u{301}e", true, true),
+            (2, 4, "e", "ae\u{301}e", false, false),
+            (1, 2, "e\u{301}", "e\u{301}x", true, true),
+            (2, 3, "\u{1F600}", "a\u{1F600}b", true, true),
+        ];
+
+        for (i, &(min, max, check, pswd, by_chars, by_graphemes)) in tests.iter().enumerate() {
+            let pswd = Password {
+                min,
+                max,
+                check,
+                pswd,
+            };
+
+            assert_eq!(chars.is_valid(&pswd), by_chars, "{}", i);
+            assert_eq!(graphemes.is_valid(&pswd), by_graphemes, "{}", i);
+        }
+    }
+
+    #[test]
+    fn out_of_range_test() {
+        let policy = PositionXor(PositionUnit::Chars);
+
+        let tests = [
+            (
+                "1-10 a: abc",
+                "position 10 is outside the password (length 3)",
+            ),
+            (
+                "0-1 a: abc",
+                "position 0 is outside the password (length 3)",
+            ),
+            (
+                "1-4 é: ééé",
+                "position 4 is outside the password (length 3)",
+            ),
+        ];
+
+        for (line, reason) in &tests {
+            let pswd = Password::parse(line).unwrap();
+
+            assert!(!policy.is_valid(&pswd), "{}", line);
+            assert_eq!(policy.explain(&pswd).to_string(), *reason, "{}", line);
+        }
+
+        // Positions a policy asks about outside the password just don't match.
+        let policy = AtLeastPositions {
+            positions: vec![0, 1, 7],
+            count: 1,
+            unit: PositionUnit::Chars,
+        };
+        assert!(policy.is_valid(&Password::parse("1-2 a: abc").unwrap()));
+        assert!(!policy.is_valid(&Password::parse("1-2 b: abc").unwrap()));
+    }
 }
@@ -1,3 +1,8 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::RangeInclusive,
+};
+
 use aoc_lib::{Bench, BenchResult, Day, ParseResult, UserError};
 use color_eyre::{
     eyre::{eyre, Result},
@@ -9,7 +14,12 @@ pub const DAY: Day = Day {
     name: "Toboggan Trajectory",
     part_1: run_part1,
     part_2: Some(run_part2),
-    other: &[("Parse", run_parse)],
+    other: &[
+        ("Parse", run_parse),
+        ("Fewest Trees", run_fewest_trees),
+        ("Most Trees", run_most_trees),
+        ("Least Trees Path", run_least_trees),
+    ],
 };
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
@@ -24,6 +34,37 @@ fn run_part2(input: &str, b: Bench) -> BenchResult {
     b.bench(|| part2(&map))
 }
 
+fn run_fewest_trees(input: &str, b: Bench) -> BenchResult {
+    let map = Map::parse(input).map_err(UserError)?;
+
+    b.bench(|| {
+        let ((dx, dy), trees) = map.search_slopes(-7..=7, 1..=4, SearchGoal::FewestTrees)?;
+        Ok::<_, Report>(format!("({}, {}): {}", dx, dy, trees))
+    })
+}
+
+fn run_most_trees(input: &str, b: Bench) -> BenchResult {
+    let map = Map::parse(input).map_err(UserError)?;
+
+    b.bench(|| {
+        let ((dx, dy), trees) = map.search_slopes(-7..=7, 1..=4, SearchGoal::MostTrees)?;
+        Ok::<_, Report>(format!("({}, {}): {}", dx, dy, trees))
+    })
+}
+
+fn run_least_trees(input: &str, b: Bench) -> BenchResult {
+    let map = Map::parse(input).map_err(UserError)?;
+
+    b.bench(|| {
+        let route = map.least_trees(&STEP_MOVES)?;
+        Ok::<_, Report>(format!(
+            "{} trees in {} moves",
+            route.cost,
+            route.path.len() - 1
+        ))
+    })
+}
+
 fn run_parse(input: &str, b: Bench) -> BenchResult {
     b.bench(|| {
         let data = Map::parse(input)?;
@@ -45,6 +86,13 @@ impl Tile {
             _ => Err(eyre!("Unknown tile: {}", ch)),
         }
     }
+
+    fn symbol(self) -> char {
+        match self {
+            Tile::Open => '.',
+            Tile::Tree => '#',
+        }
+    }
 }
 
 struct Map {
@@ -76,8 +124,19 @@ impl Map {
         }
     }
 
-    fn count_trees(&self, delta_x: usize, delta_y: usize) -> Result<u32> {
-        let width = self.tiles.len() / self.height;
+    fn width(&self) -> usize {
+        self.tiles.len() / self.height
+    }
+
+    // The map repeats horizontally, so delta_x can go either way, but we must move down.
+    fn count_trees(&self, delta_x: isize, delta_y: usize) -> Result<u32> {
+        if delta_y == 0 {
+            return Err(eyre!("Slope must move downwards"));
+        }
+
+        let width = self.width() as isize;
+        let delta_x = delta_x.rem_euclid(width) as usize;
+        let width = width as usize;
 
         Ok((1..)
             .map(|y| y * delta_y)
@@ -86,28 +145,229 @@ impl Map {
             .map(|(y, x)| self.tiles[y * width + x] as u32)
             .sum())
     }
+
+    fn survey(&self, slopes: &[(isize, usize)]) -> Result<SlopeSurvey> {
+        let trees = slopes
+            .iter()
+            .map(|&(dx, dy)| Ok(((dx, dy), self.count_trees(dx, dy)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let product = trees
+            .iter()
+            .try_fold(1u64, |acc, &(_, count)| acc.checked_mul(count as u64))
+            .ok_or_else(|| eyre!("Tree product overflowed"))?;
+
+        Ok(SlopeSurvey { trees, product })
+    }
+
+    // Ties go to the slope with the smallest delta_y, then the smallest delta_x.
+    fn search_slopes(
+        &self,
+        delta_x: RangeInclusive<isize>,
+        delta_y: RangeInclusive<usize>,
+        goal: SearchGoal,
+    ) -> Result<((isize, usize), u32)> {
+        let mut best: Option<((isize, usize), u32)> = None;
+
+        for dy in delta_y {
+            for dx in delta_x.clone() {
+                let trees = self.count_trees(dx, dy)?;
+                let is_better = match (best, goal) {
+                    (None, _) => true,
+                    (Some((_, best)), SearchGoal::FewestTrees) => trees < best,
+                    (Some((_, best)), SearchGoal::MostTrees) => trees > best,
+                };
+
+                if is_better {
+                    best = Some(((dx, dy), trees));
+                }
+            }
+        }
+
+        best.ok_or_else(|| eyre!("No slopes to search"))
+    }
+
+    // Finds the route from any tile on the top row to any tile on the bottom row which hits
+    // the fewest trees, counting the tiles at both ends. Every move goes down, so visiting
+    // tiles in order is enough to settle each one.
+    fn least_trees(&self, moves: &[(isize, usize)]) -> Result<Route> {
+        if moves.is_empty() {
+            return Err(eyre!("No moves given"));
+        }
+        if moves.iter().any(|&(_, dy)| dy == 0) {
+            return Err(eyre!("Moves must go downwards"));
+        }
+
+        let width = self.width();
+        // The fewest trees hit getting to each tile, and the tile we came from. Routes start
+        // on the top row, so those tiles have nowhere to come from.
+        let mut best: Vec<Option<(u32, Option<usize>)>> = vec![None; self.tiles.len()];
+        for (x, &tile) in self.tiles[..width].iter().enumerate() {
+            best[x] = Some((tile as u32, None));
+        }
+
+        for idx in 0..self.tiles.len() {
+            let cost = match best[idx] {
+                Some((cost, _)) => cost,
+                None => continue,
+            };
+            let (x, y) = (idx % width, idx / width);
+
+            for &(dx, dy) in moves {
+                let y = y + dy;
+                if y >= self.height {
+                    continue;
+                }
+
+                let x = (x as isize + dx).rem_euclid(width as isize) as usize;
+                let next = y * width + x;
+                let cost = cost + self.tiles[next] as u32;
+
+                if !matches!(best[next], Some((prev, _)) if prev <= cost) {
+                    best[next] = Some((cost, Some(idx)));
+                }
+            }
+        }
+
+        let (cost, end) = ((self.height - 1) * width..self.tiles.len())
+            .filter_map(|idx| best[idx].map(|(cost, _)| (cost, idx)))
+            .min_by_key(|&(cost, _)| cost)
+            .ok_or_else(|| eyre!("Bottom of the map is unreachable"))?;
+
+        let mut path = vec![end];
+        let mut idx = end;
+        while let Some((_, Some(prev))) = best[idx] {
+            path.push(prev);
+            idx = prev;
+        }
+
+        let path = path
+            .into_iter()
+            .rev()
+            .map(|idx| (idx % width, idx / width))
+            .collect();
+
+        Ok(Route { cost, path })
+    }
 }
 
-fn part2(map: &Map) -> Result<u32> {
-    [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)]
-        .iter()
-        .copied()
-        .map(|(dx, dy)| map.count_trees(dx, dy))
-        .try_fold(1, |acc, item| item.map(|i| acc * i))
+impl Display for Map {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for row in self.tiles.chunks(self.width()) {
+            let row: String = row.iter().map(|t| t.symbol()).collect();
+            writeln!(f, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
+
+const STEP_MOVES: [(isize, usize); 3] = [(-1, 1), (0, 1), (1, 1)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Route {
+    cost: u32,
+    // (x, y) of each tile visited, starting on the top row.
+    path: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SlopeSurvey {
+    trees: Vec<((isize, usize), u32)>,
+    product: u64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum SearchGoal {
+    FewestTrees,
+    MostTrees,
+}
+
+fn part2(map: &Map) -> Result<u64> {
+    map.survey(&[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)])
+        .map(|survey| survey.product)
 }
 
 #[cfg(test)]
 mod tests_2003 {
+    use std::collections::HashMap;
+
     use aoc_lib::Example;
 
     use super::*;
 
+    // Only used to make test failures easier to read.
+    impl Map {
+        // Draws the map, repeated left and right as far as needed to fit every slope's path.
+        // Where paths cross, the earlier slope's marker wins.
+        fn render(&self, slopes: &[(isize, usize)], style: RenderStyle) -> Result<String> {
+            if slopes.len() > MARKERS.len() {
+                return Err(eyre!("Can only render up to {} slopes", MARKERS.len()));
+            }
+            if slopes.iter().any(|&(_, dy)| dy == 0) {
+                return Err(eyre!("Slope must move downwards"));
+            }
+
+            // Unwrapped (x, y) of each visited tile, and the slope that visited it.
+            let mut visited = HashMap::new();
+            for (i, &(dx, dy)) in slopes.iter().enumerate().rev() {
+                for (step, y) in (1..).zip((dy..self.height).step_by(dy)) {
+                    visited.insert((step * dx, y), i);
+                }
+            }
+
+            let width = self.width() as isize;
+            let xs = || visited.keys().map(|&(x, _)| x).chain(Some(0));
+            let first_col = xs().min().unwrap_or(0).div_euclid(width) * width;
+            let last_col = (xs().max().unwrap_or(0).div_euclid(width) + 1) * width;
+
+            let mut out = String::new();
+            for y in 0..self.height {
+                for x in first_col..last_col {
+                    let tile = self.tiles[y * width as usize + x.rem_euclid(width) as usize];
+
+                    let slope = match visited.get(&(x, y)) {
+                        Some(&slope) => slope,
+                        None => {
+                            out.push(tile.symbol());
+                            continue;
+                        }
+                    };
+
+                    let (open, tree) = MARKERS[slope];
+                    let marker = if tile == Tile::Tree { tree } else { open };
+                    match style {
+                        RenderStyle::Plain => out.push(marker),
+                        RenderStyle::Ansi => {
+                            out.push_str(&format!("\x1b[1;{}m{}\x1b[0m", COLOURS[slope], marker))
+                        }
+                    }
+                }
+                out.push('\n');
+            }
+
+            Ok(out)
+        }
+    }
+
+    // The (open, tree) markers and ANSI colour for each slope, in the order they're given.
+    const MARKERS: [(char, char); 4] = [('O', 'X'), ('o', 'x'), ('@', '%'), ('+', '*')];
+    const COLOURS: [u8; 4] = [32, 33, 36, 35];
+
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    enum RenderStyle {
+        Plain,
+        Ansi,
+    }
+
     #[test]
     fn part1_example() {
         let input = aoc_lib::input(3).example(Example::Part1, 1).open().unwrap();
         let map = Map::parse(&input).unwrap();
 
-        assert_eq!(7, map.count_trees(3, 1).unwrap());
+        let trees = map.count_trees(3, 1).unwrap();
+        let rendered = map.render(&[(3, 1)], RenderStyle::Plain).unwrap();
+        assert_eq!(7, trees, "\n{}", rendered);
     }
 
     #[test]
@@ -135,4 +395,185 @@ mod tests_2003 {
 
         assert_eq!(product, 336);
     }
+
+    #[test]
+    fn survey_test() {
+        let input = aoc_lib::input(3).example(Example::Part1, 1).open().unwrap();
+        let map = Map::parse(&input).unwrap();
+
+        let survey = map
+            .survey(&[(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)])
+            .unwrap();
+        assert_eq!(survey.product, 336);
+        assert_eq!(survey.trees[4], ((1, 2), 2));
+
+        assert_eq!(map.survey(&[]).unwrap().product, 1);
+        assert!(map.survey(&[(3, 1), (1, 0)]).is_err());
+    }
+
+    #[test]
+    fn signed_slope_test() {
+        let input = aoc_lib::input(3).example(Example::Part1, 1).open().unwrap();
+        let map = Map::parse(&input).unwrap();
+
+        // Wrapping left by 8 is the same as going right by 3 on an 11-wide map.
+        assert_eq!(map.count_trees(-8, 1).unwrap(), 7);
+        assert_eq!(map.count_trees(14, 1).unwrap(), 7);
+
+        // Mirror each row around the starting column.
+        let mirrored: String = input
+            .lines()
+            .map(str::trim)
+            .map(|l| l[..1].to_owned() + &l[1..].chars().rev().collect::<String>() + "\n")
+            .collect();
+        let mirrored = Map::parse(&mirrored).unwrap();
+
+        for &(dx, dy) in &[(1, 1), (3, 1), (2, 3), (5, 4), (0, 2)] {
+            assert_eq!(
+                map.count_trees(dx, dy).unwrap(),
+                mirrored.count_trees(-dx, dy).unwrap(),
+                "({}, {})",
+                dx,
+                dy
+            );
+        }
+    }
+
+    #[test]
+    fn search_test() {
+        let input = aoc_lib::input(3).example(Example::Part1, 1).open().unwrap();
+        let map = Map::parse(&input).unwrap();
+
+        let (slope, trees) = map
+            .search_slopes(1..=7, 1..=1, SearchGoal::MostTrees)
+            .unwrap();
+        assert_eq!((slope, trees), ((3, 1), 7));
+
+        let (slope, trees) = map
+            .search_slopes(1..=7, 1..=2, SearchGoal::FewestTrees)
+            .unwrap();
+        assert_eq!((slope, trees), ((5, 2), 0));
+
+        assert!(map
+            .search_slopes(1..=7, 0..=1, SearchGoal::MostTrees)
+            .is_err());
+    }
+
+    #[test]
+    fn least_trees_test() {
+        let input = aoc_lib::input(3).example(Example::Part1, 1).open().unwrap();
+        let map = Map::parse(&input).unwrap();
+
+        // With only one move it has to follow the slope, but starting from column 7 only
+        // hits 2 trees instead of the 7 from column 0.
+        let route = map.least_trees(&[(3, 1)]).unwrap();
+        assert_eq!(route.cost, 2);
+        assert_eq!(route.path.len(), 11);
+        assert_eq!(route.path[0], (7, 0));
+        assert_eq!(route.path[4], (8, 4));
+
+        let route = map.least_trees(&STEP_MOVES).unwrap();
+        assert_eq!(route.cost, 0);
+        assert_eq!(route.path.len(), map.height);
+
+        let tree_count: u32 = route
+            .path
+            .iter()
+            .map(|&(x, y)| map.tiles[y * map.width() + x] as u32)
+            .sum();
+        assert_eq!(tree_count, route.cost);
+
+        assert!(map.least_trees(&[]).is_err());
+        assert!(map.least_trees(&[(1, 1), (1, 0)]).is_err());
+    }
+
+    #[test]
+    fn least_trees_knight_test() {
+        let map = Map::parse("...\n...\n###\n...\n#..").unwrap();
+
+        // Both bottom tiles cost the same, so the leftmost wins.
+        let route = map.least_trees(&[(1, 2), (-1, 2)]).unwrap();
+        assert_eq!(route.cost, 1);
+        assert_eq!(route.path, [(1, 0), (0, 2), (1, 4)]);
+
+        // Only even rows can be reached, so the bottom is out of reach.
+        let map = Map::parse("...\n...\n...\n...").unwrap();
+        assert!(map.least_trees(&[(1, 2), (-2, 2)]).is_err());
+    }
+
+    #[test]
+    fn least_trees_start_test() {
+        let map = Map::parse("#..#\n#...\n##.#\n#..#").unwrap();
+
+        // Going straight down from the left column hits every tree in it.
+        let route = map.least_trees(&[(0, 1)]).unwrap();
+        assert_eq!(route.cost, 0);
+        assert_eq!(route.path, [(2, 0), (2, 1), (2, 2), (2, 3)]);
+
+        // Starting on a tree counts it.
+        let map = Map::parse("##\n#.").unwrap();
+        let route = map.least_trees(&[(0, 1)]).unwrap();
+        assert_eq!(route.cost, 1);
+        assert_eq!(route.path, [(1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn render_test() {
+        let input = aoc_lib::input(3).example(Example::Part1, 1).open().unwrap();
+        let map = Map::parse(&input).unwrap();
+
+        let expected = [
+            "..##.........##.........##.......",
+            "#..O#...#..#...#...#..#...#...#..",
+            ".#....X..#..#....#..#..#....#..#.",
+            "..#.#...#O#..#.#...#.#..#.#...#.#",
+            ".#...##..#..X...##..#..#...##..#.",
+            "..#.##.......#.X#.......#.##.....",
+            ".#.#.#....#.#.#.#.O..#.#.#.#....#",
+            ".#........#.#........X.#........#",
+            "#.##...#...#.##...#...#.X#...#...",
+            "#...##....##...##....##...#X....#",
+            ".#..#...#.#.#..#...#.#.#..#...X.#",
+        ];
+        let actual = map.render(&[(3, 1)], RenderStyle::Plain).unwrap();
+        assert_eq!(actual.lines().collect::<Vec<_>>(), expected);
+
+        assert_eq!(map.to_string(), input.replace(' ', ""));
+    }
+
+    #[test]
+    fn render_multiple_test() {
+        let input = aoc_lib::input(3).example(Example::Part1, 1).open().unwrap();
+        let map = Map::parse(&input).unwrap();
+
+        // Going left means the start is in the last copy of the map.
+        let rendered = map.render(&[(1, 2), (-2, 1)], RenderStyle::Plain).unwrap();
+        let rows: Vec<_> = rendered.lines().collect();
+        assert_eq!(rows.len(), map.height);
+        assert!(rows.iter().all(|r| r.len() == 33));
+        assert_eq!(&rows[1][20..], "o.#...#...#..");
+        assert_eq!(&rows[2][18..], "o.#..X....#..#.");
+        assert_eq!(&rows[10][..3], ".#o");
+
+        let plain = map.render(&[(3, 1), (1, 2)], RenderStyle::Plain).unwrap();
+        let ansi = map.render(&[(3, 1), (1, 2)], RenderStyle::Ansi).unwrap();
+        assert!(ansi.contains("\x1b[1;32mX\x1b[0m"));
+        assert!(ansi.contains("\x1b[1;33mo\x1b[0m"));
+
+        let stripped = ansi
+            .split("\x1b[")
+            .enumerate()
+            .map(|(i, part)| {
+                if i == 0 {
+                    part
+                } else {
+                    &part[part.find('m').unwrap() + 1..]
+                }
+            })
+            .collect::<String>();
+        assert_eq!(stripped, plain);
+
+        assert!(map.render(&[(1, 0)], RenderStyle::Plain).is_err());
+        assert!(map.render(&[(1, 1); 5], RenderStyle::Plain).is_err());
+    }
 }
@@ -1,30 +1,75 @@
-use std::num::ParseIntError;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display, Formatter},
+    ops::RangeInclusive,
+};
 
 use aoc_lib::{parsers::split_pair, Bench, BenchResult, Day, NoError, ParseResult, UserError};
 use color_eyre::{
     eyre::{eyre, Result},
     Report,
 };
+use itertools::Itertools;
 use nom::bytes::complete::take_while;
 
+use super::common::{json_string, split_records};
+
 pub const DAY: Day = Day {
     day: 4,
     name: "Passport Processing",
     part_1: run_part1,
     part_2: Some(run_part2),
-    other: &[("Parse", run_parse)],
+    other: &[
+        ("Parse", run_parse),
+        ("Field Errors", run_field_errors),
+        ("Part 2 Lenient", run_part2_lenient),
+        ("Write Batch", run_write_batch),
+        ("Write JSON", run_write_json),
+    ],
 };
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
     let passports = Passport::parse_passports(input).map_err(UserError)?;
 
-    b.bench(|| Ok::<_, NoError>(passports.iter().filter(|p| p.is_valid_part1()).count()))
+    b.bench(|| Ok::<_, NoError>(PART1_SCHEMA.count_valid(&passports)))
 }
 
 fn run_part2(input: &str, b: Bench) -> BenchResult {
     let passports = Passport::parse_passports(input).map_err(UserError)?;
 
-    b.bench(|| Ok::<_, NoError>(passports.iter().filter(|p| p.is_valid_part2()).count()))
+    b.bench(|| Ok::<_, NoError>(PART2_SCHEMA.count_valid(&passports)))
+}
+
+fn run_field_errors(input: &str, b: Bench) -> BenchResult {
+    let passports = Passport::parse_passports(input).map_err(UserError)?;
+
+    b.bench(|| {
+        let errors: usize = passports
+            .iter()
+            .map(|p| PART2_SCHEMA.validate(p).len())
+            .sum();
+        Ok::<_, NoError>(errors)
+    })
+}
+
+fn run_part2_lenient(input: &str, b: Bench) -> BenchResult {
+    b.bench(|| {
+        let parsed = Passport::parse_passports_lenient(input);
+        let valid = PART2_SCHEMA.count_valid(&parsed.passports);
+        Ok::<_, NoError>(format!("{} ({} warnings)", valid, parsed.warnings.len()))
+    })
+}
+
+fn run_write_batch(input: &str, b: Bench) -> BenchResult {
+    let passports = Passport::parse_passports(input).map_err(UserError)?;
+
+    b.bench(|| Ok::<_, NoError>(write_batch(&passports, 80).len()))
+}
+
+fn run_write_json(input: &str, b: Bench) -> BenchResult {
+    let passports = Passport::parse_passports(input).map_err(UserError)?;
+
+    b.bench(|| Ok::<_, NoError>(write_json(&passports, &PART2_SCHEMA).len()))
 }
 
 fn run_parse(input: &str, b: Bench) -> BenchResult {
@@ -44,12 +89,62 @@ struct Passport<'a> {
     eye_color: Option<&'a str>,
     passport_id: Option<&'a str>,
     country_id: Option<u16>,
+    extras: BTreeMap<&'a str, &'a str>,
+}
+
+const FIELDS: [&str; 8] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"];
+
+#[derive(Debug, PartialEq)]
+struct LenientParse<'a> {
+    passports: Vec<Passport<'a>>,
+    warnings: Vec<ParseWarning<'a>>,
+}
+
+// 1-based, with columns counted in characters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ParseWarning<'a> {
+    position: Position,
+    kind: WarningKind<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum WarningKind<'a> {
+    MissingSeparator(&'a str),
+    UnknownField(&'a str),
+    DuplicateField { key: &'a str, first: Position },
+    InvalidValue { key: &'a str, value: &'a str },
+}
+
+impl Display for ParseWarning<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Position { line, column } = self.position;
+        write!(f, "{}:{}: ", line, column)?;
+
+        match self.kind {
+            WarningKind::MissingSeparator(token) => write!(f, "missing ':' in {}", token),
+            WarningKind::UnknownField(key) => write!(f, "unknown field {}", key),
+            WarningKind::DuplicateField { key, first } => write!(
+                f,
+                "duplicate field {}, first seen at {}:{}",
+                key, first.line, first.column
+            ),
+            WarningKind::InvalidValue { key, value } => {
+                write!(f, "invalid value for {}: {}", key, value)
+            }
+        }
+    }
 }
 
 impl<'a> Passport<'a> {
     fn parse_passports(input: &'a str) -> Result<Vec<Passport<'a>>> {
         let mut passports = Vec::new();
-        let raw_passports = input.split("\n\n");
+        let raw_passports = split_records(input);
 
         for raw_passport in raw_passports {
             let passport_fields = raw_passport
@@ -62,17 +157,7 @@ impl<'a> Passport<'a> {
             for field in passport_fields {
                 let (field, value) = split_pair(field, ":")?;
 
-                match field {
-                    "byr" => passport.birth_year = Some(value.parse()?),
-                    "iyr" => passport.issue_year = Some(value.parse()?),
-                    "eyr" => passport.expiration_year = Some(value.parse()?),
-                    "hgt" => passport.height = Some(value),
-                    "hcl" => passport.hair_color = Some(value),
-                    "ecl" => passport.eye_color = Some(value),
-                    "pid" => passport.passport_id = Some(value),
-                    "cid" => passport.country_id = Some(value.parse()?),
-                    _ => return Err(eyre!("Unknown field: {}", field)),
-                }
+                passport.set_field(field, value)?;
             }
 
             passports.push(passport);
@@ -81,57 +166,423 @@ impl<'a> Passport<'a> {
         Ok(passports)
     }
 
-    fn is_valid_part1(&self) -> bool {
-        matches!(
-            self,
-            Passport {
-                birth_year: Some(_),
-                issue_year: Some(_),
-                expiration_year: Some(_),
-                height: Some(_),
-                hair_color: Some(_),
-                eye_color: Some(_),
-                passport_id: Some(_),
-                country_id: _,
+    // Like `parse_passports`, but unknown fields are kept in `extras`, and anything odd is
+    // recorded as a warning rather than failing the whole batch.
+    fn parse_passports_lenient(input: &'a str) -> LenientParse<'a> {
+        let mut passports = Vec::new();
+        let mut warnings = Vec::new();
+
+        // Line numbers are counted up to each record as we reach it.
+        let mut line_start = 0;
+        let mut line_no = 1;
+
+        for record in split_records(input) {
+            // Each record is a slice of the input, so this is its byte offset.
+            let record_start = record.as_ptr() as usize - input.as_ptr() as usize;
+            line_no += input[line_start..record_start].matches('\n').count();
+            line_start = record_start;
+
+            let mut passport = Passport::default();
+            // Where each key in this passport was first seen.
+            let mut seen = HashMap::new();
+
+            for (line_idx, line) in record.lines().enumerate() {
+                for token in line.split_whitespace() {
+                    // The token is a slice of the line, so this is its byte offset.
+                    let offset = token.as_ptr() as usize - line.as_ptr() as usize;
+                    let position = Position {
+                        line: line_no + line_idx,
+                        column: line[..offset].chars().count() + 1,
+                    };
+                    let mut warn = |kind| warnings.push(ParseWarning { position, kind });
+
+                    let (key, value) = match split_pair(token, ":") {
+                        Ok(pair) => pair,
+                        Err(_) => {
+                            warn(WarningKind::MissingSeparator(token));
+                            continue;
+                        }
+                    };
+
+                    if let Some(&first) = seen.get(key) {
+                        warn(WarningKind::DuplicateField { key, first });
+                    } else {
+                        seen.insert(key, position);
+                    }
+
+                    if !FIELDS.contains(&key) {
+                        warn(WarningKind::UnknownField(key));
+                        passport.extras.insert(key, value);
+                    } else if passport.set_field(key, value).is_err() {
+                        warn(WarningKind::InvalidValue { key, value });
+                    }
+                }
             }
-        )
+
+            passports.push(passport);
+        }
+
+        LenientParse {
+            passports,
+            warnings,
+        }
+    }
+
+    fn set_field(&mut self, key: &'a str, value: &'a str) -> Result<()> {
+        match key {
+            "byr" => self.birth_year = Some(value.parse()?),
+            "iyr" => self.issue_year = Some(value.parse()?),
+            "eyr" => self.expiration_year = Some(value.parse()?),
+            "hgt" => self.height = Some(value),
+            "hcl" => self.hair_color = Some(value),
+            "ecl" => self.eye_color = Some(value),
+            "pid" => self.passport_id = Some(value),
+            "cid" => self.country_id = Some(value.parse()?),
+            _ => return Err(eyre!("Unknown field: {}", key)),
+        }
+
+        Ok(())
     }
 
-    fn is_height_valid(hgt: &str) -> bool {
-        let v = take_while::<_, _, ()>(|c: char| c.is_ascii_digit())(hgt)
-            .map(|(unit, val)| Ok::<_, ParseIntError>((unit, val.parse::<u8>()?)));
+    // The known fields in puzzle order, followed by any extras ordered by key.
+    fn fields(&self) -> impl Iterator<Item = (&'a str, FieldValue<'a>)> + '_ {
+        FIELDS
+            .iter()
+            .filter_map(move |&key| self.get(key).map(|value| (key, value)))
+            .chain(
+                self.extras
+                    .iter()
+                    .map(|(&key, &value)| (key, FieldValue::Text(value))),
+            )
+    }
+
+    // Writes the fields as `key:value` pairs, starting a new line rather than going past
+    // `max_width` characters.
+    fn write_batch(&self, max_width: usize) -> String {
+        let mut out = String::new();
+        let mut line_len = 0;
+
+        for (key, value) in self.fields() {
+            let field = format!("{}:{}", key, value);
+            let field_len = field.chars().count();
+
+            if line_len > 0 && line_len + 1 + field_len > max_width {
+                out.push('\n');
+                line_len = 0;
+            } else if line_len > 0 {
+                out.push(' ');
+                line_len += 1;
+            }
+
+            out.push_str(&field);
+            line_len += field_len;
+        }
+
+        out
+    }
+
+    fn get(&self, key: &str) -> Option<FieldValue<'a>> {
+        match key {
+            "byr" => self.birth_year.map(FieldValue::Number),
+            "iyr" => self.issue_year.map(FieldValue::Number),
+            "eyr" => self.expiration_year.map(FieldValue::Number),
+            "hgt" => self.height.map(FieldValue::Text),
+            "hcl" => self.hair_color.map(FieldValue::Text),
+            "ecl" => self.eye_color.map(FieldValue::Text),
+            "pid" => self.passport_id.map(FieldValue::Text),
+            "cid" => self.country_id.map(FieldValue::Number),
+            _ => self.extras.get(key).copied().map(FieldValue::Text),
+        }
+    }
+}
+
+fn write_batch(passports: &[Passport], max_width: usize) -> String {
+    passports
+        .iter()
+        .map(|p| p.write_batch(max_width) + "\n")
+        .join("\n")
+}
+
+// One object per line, holding the passport's fields and how it fared against `schema`.
+fn write_json(passports: &[Passport], schema: &Schema) -> String {
+    let mut json = String::from("[\n");
+
+    for (i, passport) in passports.iter().enumerate() {
+        let fields = passport
+            .fields()
+            .map(|(key, value)| {
+                let value = match value {
+                    FieldValue::Number(n) => n.to_string(),
+                    FieldValue::Text(text) => json_string(text),
+                };
+                format!("{}: {}", json_string(key), value)
+            })
+            .join(", ");
+
+        let errors = schema.validate(passport);
+        let valid = errors.is_empty();
+        let errors = errors
+            .iter()
+            .map(|e| json_string(&e.to_string()))
+            .join(", ");
+
+        let separator = if i + 1 < passports.len() { "," } else { "" };
+        json.push_str(&format!(
+            "  {{\"fields\": {{{}}}, \"valid\": {}, \"errors\": [{}]}}{}\n",
+            fields, valid, errors, separator
+        ));
+    }
+
+    json.push_str("]\n");
+    json
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum FieldValue<'a> {
+    Number(u16),
+    Text(&'a str),
+}
 
-        matches!(v, Ok(Ok(("cm", 150..=193))) | Ok(Ok(("in", 59..=76))))
+impl Display for FieldValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Number(n) => write!(f, "{}", n),
+            FieldValue::Text(s) => write!(f, "{}", s),
+        }
     }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Rule {
+    Any,
+    Year(RangeInclusive<u16>),
+    Height {
+        cm: RangeInclusive<u16>,
+        inches: RangeInclusive<u16>,
+    },
+    HexColour,
+    OneOf(&'static [&'static str]),
+    Digits(usize),
+}
 
-    fn is_hair_color_valid(hcl: &str) -> bool {
-        if !hcl.starts_with('#') {
-            return false;
+impl Rule {
+    // On failure, returns why the value doesn't fit, to go after the value in an error.
+    fn check(&self, value: FieldValue) -> Result<(), String> {
+        let text = || match value {
+            FieldValue::Text(text) => Ok(text),
+            FieldValue::Number(_) => Err("is not text".to_owned()),
+        };
+
+        match self {
+            Rule::Any => Ok(()),
+            Rule::Year(range) => {
+                let year = match value {
+                    FieldValue::Number(year) => year,
+                    FieldValue::Text(text) => {
+                        text.parse().map_err(|_| "is not a year".to_owned())?
+                    }
+                };
+
+                check_range(year, range)
+            }
+            Rule::Height { cm, inches } => {
+                let (unit, height) = take_while::<_, _, ()>(|c: char| c.is_ascii_digit())(text()?)
+                    .map_err(|_| "is not a height".to_owned())?;
+                let height: u16 = height.parse().map_err(|_| "is not a height".to_owned())?;
+
+                match unit {
+                    "cm" => check_range(height, cm),
+                    "in" => check_range(height, inches),
+                    "" => Err("has no unit".to_owned()),
+                    _ => Err(format!("has unknown unit {}", unit)),
+                }
+            }
+            Rule::HexColour => match text()?.strip_prefix('#') {
+                Some(hex) if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) => Ok(()),
+                _ => Err("is not a hex colour".to_owned()),
+            },
+            Rule::OneOf(options) => {
+                let text = text()?;
+                if options.contains(&text) {
+                    Ok(())
+                } else {
+                    Err(format!("is not one of {}", options.join(", ")))
+                }
+            }
+            Rule::Digits(len) => {
+                let text = text()?;
+                if text.len() == *len && text.chars().all(|c| c.is_ascii_digit()) {
+                    Ok(())
+                } else {
+                    Err(format!("is not {} digits", len))
+                }
+            }
         }
+    }
+}
 
-        hcl[1..].chars().all(|c| c.is_ascii_hexdigit()) && hcl.len() == 7
+fn check_range(value: u16, range: &RangeInclusive<u16>) -> Result<(), String> {
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(format!("out of {:?}", range))
     }
+}
 
-    fn is_passport_id_valid(pid: &str) -> bool {
-        pid.chars().all(|c| c.is_ascii_digit()) && pid.len() == 9
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FieldSpec {
+    key: &'static str,
+    required: bool,
+    rule: Rule,
+}
+
+impl FieldSpec {
+    fn check(&self, passport: &Passport) -> Result<(), FieldError> {
+        let value = match passport.get(self.key) {
+            Some(value) => value,
+            None if self.required => return Err(FieldError::Missing(self.key)),
+            None => return Ok(()),
+        };
+
+        self.rule
+            .check(value)
+            .map_err(|reason| FieldError::Invalid {
+                key: self.key,
+                value: value.to_string(),
+                reason,
+            })
     }
+}
 
-    fn is_valid_part2(&self) -> bool {
-        matches!(self, Passport {
-                birth_year: Some(1920..=2002),
-                issue_year: Some(2010..=2020),
-                expiration_year: Some(2020..=2030),
-                height: Some(hgt),
-                hair_color: Some(hcl),
-                eye_color: Some("amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth"),
-                passport_id: Some(pid),
-                country_id: _,
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FieldError {
+    Missing(&'static str),
+    Invalid {
+        key: &'static str,
+        value: String,
+        reason: String,
+    },
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::Missing(key) => write!(f, "{}: missing", key),
+            FieldError::Invalid { key, value, reason } => {
+                write!(f, "{}: {} {}", key, value, reason)
             }
-            if Passport::is_height_valid(hgt) && Passport::is_hair_color_valid(hcl) && Passport::is_passport_id_valid(pid)
-        )
+        }
+    }
+}
+
+struct Schema(&'static [FieldSpec]);
+
+impl Schema {
+    fn validate(&self, passport: &Passport) -> Vec<FieldError> {
+        self.0
+            .iter()
+            .filter_map(|spec| spec.check(passport).err())
+            .collect()
+    }
+
+    fn is_valid(&self, passport: &Passport) -> bool {
+        self.0.iter().all(|spec| spec.check(passport).is_ok())
+    }
+
+    fn count_valid(&self, passports: &[Passport]) -> usize {
+        passports.iter().filter(|p| self.is_valid(p)).count()
     }
 }
 
+const PART1_SCHEMA: Schema = Schema(&[
+    FieldSpec {
+        key: "byr",
+        required: true,
+        rule: Rule::Any,
+    },
+    FieldSpec {
+        key: "iyr",
+        required: true,
+        rule: Rule::Any,
+    },
+    FieldSpec {
+        key: "eyr",
+        required: true,
+        rule: Rule::Any,
+    },
+    FieldSpec {
+        key: "hgt",
+        required: true,
+        rule: Rule::Any,
+    },
+    FieldSpec {
+        key: "hcl",
+        required: true,
+        rule: Rule::Any,
+    },
+    FieldSpec {
+        key: "ecl",
+        required: true,
+        rule: Rule::Any,
+    },
+    FieldSpec {
+        key: "pid",
+        required: true,
+        rule: Rule::Any,
+    },
+    FieldSpec {
+        key: "cid",
+        required: false,
+        rule: Rule::Any,
+    },
+]);
+
+const PART2_SCHEMA: Schema = Schema(&[
+    FieldSpec {
+        key: "byr",
+        required: true,
+        rule: Rule::Year(1920..=2002),
+    },
+    FieldSpec {
+        key: "iyr",
+        required: true,
+        rule: Rule::Year(2010..=2020),
+    },
+    FieldSpec {
+        key: "eyr",
+        required: true,
+        rule: Rule::Year(2020..=2030),
+    },
+    FieldSpec {
+        key: "hgt",
+        required: true,
+        rule: Rule::Height {
+            cm: 150..=193,
+            inches: 59..=76,
+        },
+    },
+    FieldSpec {
+        key: "hcl",
+        required: true,
+        rule: Rule::HexColour,
+    },
+    FieldSpec {
+        key: "ecl",
+        required: true,
+        rule: Rule::OneOf(&["amb", "blu", "brn", "gry", "grn", "hzl", "oth"]),
+    },
+    FieldSpec {
+        key: "pid",
+        required: true,
+        rule: Rule::Digits(9),
+    },
+    FieldSpec {
+        key: "cid",
+        required: false,
+        rule: Rule::Any,
+    },
+]);
+
 #[cfg(test)]
 mod tests_2004 {
     use aoc_lib::Example;
@@ -152,6 +603,7 @@ mod tests_2004 {
                 issue_year: Some(2017),
                 country_id: Some(147),
                 height: Some("183cm"),
+                extras: BTreeMap::new(),
             },
             Passport {
                 issue_year: Some(2013),
@@ -198,7 +650,261 @@ mod tests_2004 {
         let expected = [true, false, true, false];
 
         for (i, (passport, expected)) in passports.iter().zip(&expected).enumerate() {
-            assert_eq!(passport.is_valid_part1(), *expected, "{}", i);
+            assert_eq!(PART1_SCHEMA.is_valid(passport), *expected, "{}", i);
+        }
+    }
+
+    #[test]
+    fn part2_example() {
+        let invalid = "eyr:1972 cid:100
+            hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
+
+            iyr:2019
+            hcl:#602927 eyr:1967 hgt:170cm
+            ecl:grn pid:012533040 byr:1946
+
+            hcl:dab227 iyr:2012
+            ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
+
+            hgt:59cm ecl:zzz
+            eyr:2038 hcl:74454a iyr:2023
+            pid:3556412378 byr:2007";
+
+        let valid = "pid:087499704 hgt:74in ecl:grn iyr:2012 eyr:2030 byr:1980
+            hcl:#623a2f
+
+            eyr:2029 ecl:blu cid:129 byr:1989
+            iyr:2014 pid:896056539 hcl:#a97842 hgt:165cm
+
+            hcl:#888785
+            hgt:164cm byr:2001 iyr:2015 cid:88
+            pid:545766238 ecl:hzl
+            eyr:2022
+
+            iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
+
+        let invalid = Passport::parse_passports(invalid).unwrap();
+        let valid = Passport::parse_passports(valid).unwrap();
+
+        assert_eq!(invalid.len(), 4);
+        assert_eq!(valid.len(), 4);
+        assert_eq!(PART2_SCHEMA.count_valid(&invalid), 0);
+        assert_eq!(PART2_SCHEMA.count_valid(&valid), 4);
+        assert_eq!(PART1_SCHEMA.count_valid(&invalid), 4);
+    }
+
+    #[test]
+    fn rule_test() {
+        let height = &PART2_SCHEMA.0[3].rule;
+        let tests = [
+            (&PART2_SCHEMA.0[0].rule, FieldValue::Number(2002), true),
+            (&PART2_SCHEMA.0[0].rule, FieldValue::Number(2003), false),
+            (&PART2_SCHEMA.0[0].rule, FieldValue::Text("2002"), true),
+            (&PART2_SCHEMA.0[0].rule, FieldValue::Text("19x0"), false),
+            (height, FieldValue::Text("60in"), true),
+            (height, FieldValue::Text("190cm"), true),
+            (height, FieldValue::Text("190in"), false),
+            (height, FieldValue::Text("190"), false),
+            (height, FieldValue::Text("cm"), false),
+            (height, FieldValue::Number(60), false),
+            (&Rule::HexColour, FieldValue::Text("#123abc"), true),
+            (&Rule::HexColour, FieldValue::Text("#123abz"), false),
+            (&Rule::HexColour, FieldValue::Text("123abc"), false),
+            (&PART2_SCHEMA.0[5].rule, FieldValue::Text("brn"), true),
+            (&PART2_SCHEMA.0[5].rule, FieldValue::Text("wat"), false),
+            (&Rule::Digits(9), FieldValue::Text("000000001"), true),
+            (&Rule::Digits(9), FieldValue::Text("0123456789"), false),
+        ];
+
+        for (rule, value, expected) in &tests {
+            assert_eq!(
+                rule.check(*value).is_ok(),
+                *expected,
+                "{:?} {}",
+                rule,
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn field_errors_test() {
+        let passports = Passport::parse_passports(
+            "hgt:190in ecl:wat hcl:#123abc pid:0123456789 byr:2003 eyr:2025 cid:5",
+        )
+        .unwrap();
+
+        let errors: Vec<_> = PART2_SCHEMA
+            .validate(&passports[0])
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        let expected = [
+            "byr: 2003 out of 1920..=2002",
+            "iyr: missing",
+            "hgt: 190in out of 59..=76",
+            "ecl: wat is not one of amb, blu, brn, gry, grn, hzl, oth",
+            "pid: 0123456789 is not 9 digits",
+        ];
+        assert_eq!(errors, expected);
+
+        assert_eq!(
+            PART1_SCHEMA.validate(&passports[0]),
+            [FieldError::Missing("iyr")]
+        );
+    }
+
+    #[test]
+    fn lenient_parse_test() {
+        let input = aoc_lib::input(4).example(Example::Part1, 1).open().unwrap();
+
+        let strict = Passport::parse_passports(&input).unwrap();
+        let lenient = Passport::parse_passports_lenient(&input);
+        assert_eq!(lenient.passports, strict);
+        assert!(lenient.warnings.is_empty());
+    }
+
+    #[test]
+    fn lenient_warnings_test() {
+        let input = "ecl:gry vnd:x1 pid:860033327\n\
+                     eyr:2020 byr:1937 ecl:blu \t oops\n\
+                     \n\
+                     \n\
+                     byr:19x7 hgt:183cm vnd:a vnd:b\n";
+
+        assert!(Passport::parse_passports(input).is_err());
+
+        let parsed = Passport::parse_passports_lenient(input);
+
+        let mut extras = BTreeMap::new();
+        extras.insert("vnd", "x1");
+        let expected = [
+            Passport {
+                eye_color: Some("blu"),
+                passport_id: Some("860033327"),
+                expiration_year: Some(2020),
+                birth_year: Some(1937),
+                extras,
+                ..Default::default()
+            },
+            Passport {
+                height: Some("183cm"),
+                extras: [("vnd", "b")].iter().copied().collect(),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(parsed.passports, expected);
+
+        let warnings: Vec<_> = parsed.warnings.iter().map(ToString::to_string).collect();
+        let expected = [
+            "1:9: unknown field vnd",
+            "2:19: duplicate field ecl, first seen at 1:1",
+            "2:29: missing ':' in oops",
+            "5:1: invalid value for byr: 19x7",
+            "5:20: unknown field vnd",
+            "5:26: duplicate field vnd, first seen at 5:20",
+            "5:26: unknown field vnd",
+        ];
+        assert_eq!(warnings, expected);
+
+        // Line endings and whitespace on blank lines don't move anything.
+        let crlf = input.replace('\n', "\r\n");
+        let padded = input.replace("\n\n", "\n \t\n");
+        for other in &[crlf, padded] {
+            let other_parsed = Passport::parse_passports_lenient(other);
+            assert_eq!(other_parsed.passports, parsed.passports, "{:?}", other);
+
+            let other_warnings: Vec<_> = other_parsed
+                .warnings
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            assert_eq!(other_warnings, expected, "{:?}", other);
+        }
+
+        // Extra fields can be validated like any other.
+        let passport = &parsed.passports[1];
+        assert_eq!(passport.get("vnd"), Some(FieldValue::Text("b")));
+        assert_eq!(passport.get("xyz"), None);
+    }
+
+    #[test]
+    fn batch_round_trip_test() {
+        let input = aoc_lib::input(4).example(Example::Part1, 1).open().unwrap();
+        let passports = Passport::parse_passports(&input).unwrap();
+
+        for &width in &[0, 20, 40, 80, 1000] {
+            let written = write_batch(&passports, width);
+            assert!(
+                written
+                    .lines()
+                    .all(|l| l.len() <= width || !l.contains(' ')),
+                "{}\n{}",
+                width,
+                written
+            );
+
+            let reparsed = Passport::parse_passports(&written).unwrap();
+            assert_eq!(reparsed, passports, "{}\n{}", width, written);
         }
+
+        let expected = "byr:1937 iyr:2017 eyr:2020 hgt:183cm\n\
+                        hcl:#fffffd ecl:gry pid:860033327\n\
+                        cid:147\n";
+        assert_eq!(write_batch(&passports[..1], 40), expected);
+    }
+
+    #[test]
+    fn lenient_round_trip_test() {
+        let input = "zzz:1 ecl:gry vnd:x1\npid:0123 aaa:\"q\"\n\nhgt:59in\n";
+        let parsed = Passport::parse_passports_lenient(input);
+
+        let written = write_batch(&parsed.passports, 80);
+        assert_eq!(
+            written,
+            "ecl:gry pid:0123 aaa:\"q\" vnd:x1 zzz:1\n\nhgt:59in\n"
+        );
+
+        let reparsed = Passport::parse_passports_lenient(&written);
+        assert_eq!(reparsed.passports, parsed.passports);
+    }
+
+    #[test]
+    fn json_test() {
+        let input = "hgt:190in ecl:amb vnd:\"x\\y\" byr:1990\n\n\
+                     byr:1937 iyr:2017 eyr:2020 hgt:183cm hcl:#fffffd ecl:gry pid:860033327";
+        let parsed = Passport::parse_passports_lenient(input);
+
+        let expected = [
+            "[",
+            r#"  {"fields": {"byr": 1990, "hgt": "190in", "ecl": "amb", "vnd": "\"x\\y\""}, "valid": false, "errors": ["iyr: missing", "eyr: missing", "hgt: 190in out of 59..=76", "hcl: missing", "pid: missing"]},"#,
+            r##"  {"fields": {"byr": 1937, "iyr": 2017, "eyr": 2020, "hgt": "183cm", "hcl": "#fffffd", "ecl": "gry", "pid": "860033327"}, "valid": true, "errors": []}"##,
+            "]",
+        ];
+        let actual = write_json(&parsed.passports, &PART2_SCHEMA);
+        assert_eq!(actual.lines().collect::<Vec<_>>(), expected);
+
+        assert_eq!(write_json(&[], &PART2_SCHEMA), "[\n]\n");
+    }
+
+    #[test]
+    fn line_endings_test() {
+        let input = aoc_lib::input(4).example(Example::Part1, 1).open().unwrap();
+        let expected = Passport::parse_passports(&input).unwrap();
+
+        let crlf = input.replace('\n', "\r\n");
+        let padded = input.replace("\n\n", "\n \n\t\n");
+
+        for input in &[crlf, padded, format!("\r\n{}\r\n\r\n", input)] {
+            let actual = Passport::parse_passports(input).unwrap();
+            assert_eq!(actual, expected, "{:?}", input);
+
+            let lenient = Passport::parse_passports_lenient(input);
+            assert_eq!(lenient.passports, expected, "{:?}", input);
+            assert!(lenient.warnings.is_empty(), "{:?}", input);
+        }
+
+        assert!(Passport::parse_passports("").unwrap().is_empty());
     }
 }
@@ -1,57 +1,167 @@
-use aoc_lib::{misc::ArrWindows, Bench, BenchResult, Day};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::Chars,
+};
+
+use aoc_lib::{Bench, BenchResult, Day, NoError, UserError};
 use color_eyre::eyre::{eyre, Report, Result};
+use itertools::Itertools;
 
 pub const DAY: Day = Day {
     day: 5,
     name: "Binary Boarding",
     part_1: run_part1,
     part_2: Some(run_part2),
-    other: &[],
+    other: &[("Encode", run_encode), ("Gaps", run_gaps)],
 };
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
-    b.bench(|| part1(input))
+    b.bench(|| part1(input, &PlaneLayout::STANDARD))
 }
 
 fn run_part2(input: &str, b: Bench) -> BenchResult {
-    b.bench(|| part2(input))
+    b.bench(|| part2(input, &PlaneLayout::STANDARD))
 }
 
-fn get_seat_row(input: &str) -> Result<(u16, u16)> {
-    let (row, seat) = input.split_at(7);
+fn run_gaps(input: &str, b: Bench) -> BenchResult {
+    let cabin = Cabin::parse(input, PlaneLayout::STANDARD).map_err(UserError)?;
 
-    let row = row
-        .chars()
-        .map(|c| match c {
-            'F' => Ok(0),
-            'B' => Ok(1),
-            _ => Err(eyre!("Unexpected character: {}", c)),
-        })
-        .try_fold(0, |acc, digit| Ok::<_, Report>(acc * 2 + digit?))?;
-
-    let seat = seat
-        .chars()
-        .map(|c| match c {
-            'L' => Ok(0),
-            'R' => Ok(1),
-            _ => Err(eyre!("Unexpected character: {}", c)),
-        })
-        .try_fold(0, |acc, digit| Ok::<_, Report>(acc * 2 + digit?))?;
+    b.bench(|| {
+        let gaps = cabin.gaps();
+        let count = |kind| gaps.iter().filter(|g| g.kind == kind).count();
 
-    Ok((row, seat))
+        Ok::<_, NoError>(format!(
+            "{} front, {} back, {} interior, {} candidate",
+            count(GapKind::FrontBlock),
+            count(GapKind::BackBlock),
+            count(GapKind::Interior),
+            count(GapKind::Candidate),
+        ))
+    })
 }
 
-fn seat_id((row, seat): (u16, u16)) -> u16 {
-    row * 8 + seat
+fn run_encode(input: &str, b: Bench) -> BenchResult {
+    let layout = PlaneLayout::STANDARD;
+    let seats: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(|l| layout.decode(l))
+        .collect::<Result<_>>()
+        .map_err(UserError)?;
+
+    b.bench(|| {
+        let passes: Vec<_> = seats
+            .iter()
+            .map(|&(row, seat)| layout.encode(row, seat))
+            .collect::<Result<_>>()?;
+        Ok::<_, Report>(passes.len())
+    })
+}
+
+// Each boarding pass is the row number in binary, followed by the seat number, with each
+// axis using its own pair of characters for 0 and 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct PlaneLayout {
+    row_bits: u32,
+    col_bits: u32,
+    row_chars: (char, char),
+    col_chars: (char, char),
+}
+
+impl PlaneLayout {
+    const STANDARD: PlaneLayout = match PlaneLayout::new(7, 3, ('F', 'B'), ('L', 'R')) {
+        Ok(layout) => layout,
+        Err(_) => panic!("Invalid standard layout"),
+    };
+
+    // A `const fn`, so fixed layouts are checked at compile time.
+    const fn new(
+        row_bits: u32,
+        col_bits: u32,
+        row_chars: (char, char),
+        col_chars: (char, char),
+    ) -> Result<PlaneLayout, &'static str> {
+        if row_bits == 0 || col_bits == 0 || row_bits + col_bits > 16 {
+            return Err("Invalid layout size");
+        }
+
+        if row_chars.0 == row_chars.1 || col_chars.0 == col_chars.1 {
+            return Err("Each axis needs two different characters");
+        }
+
+        Ok(PlaneLayout {
+            row_bits,
+            col_bits,
+            row_chars,
+            col_chars,
+        })
+    }
+
+    fn rows(&self) -> u16 {
+        1 << self.row_bits
+    }
+
+    fn columns(&self) -> u16 {
+        1 << self.col_bits
+    }
+
+    fn decode(&self, pass: &str) -> Result<(u16, u16)> {
+        fn decode_axis(chars: &mut Chars, bits: u32, (zero, one): (char, char)) -> Result<u16> {
+            (0..bits).try_fold(0, |acc, _| {
+                let digit = match chars.next() {
+                    Some(c) if c == zero => 0,
+                    Some(c) if c == one => 1,
+                    Some(c) => return Err(eyre!("Unexpected character: {}", c)),
+                    None => return Err(eyre!("Boarding pass too short")),
+                };
+
+                Ok(acc * 2 + digit)
+            })
+        }
+
+        let mut chars = pass.chars();
+        let row = decode_axis(&mut chars, self.row_bits, self.row_chars)?;
+        let seat = decode_axis(&mut chars, self.col_bits, self.col_chars)?;
+
+        if chars.next().is_some() {
+            return Err(eyre!("Boarding pass too long: {}", pass));
+        }
+
+        Ok((row, seat))
+    }
+
+    fn encode(&self, row: u16, seat: u16) -> Result<String> {
+        fn encode_axis(
+            value: u16,
+            bits: u32,
+            (zero, one): (char, char),
+        ) -> impl Iterator<Item = char> {
+            (0..bits)
+                .rev()
+                .map(move |bit| if value >> bit & 1 == 1 { one } else { zero })
+        }
+
+        if row >= self.rows() || seat >= self.columns() {
+            return Err(eyre!("Seat out of range: row {}, seat {}", row, seat));
+        }
+
+        Ok(encode_axis(row, self.row_bits, self.row_chars)
+            .chain(encode_axis(seat, self.col_bits, self.col_chars))
+            .collect())
+    }
+
+    fn seat_id(&self, (row, seat): (u16, u16)) -> u16 {
+        row * self.columns() + seat
+    }
 }
 
-fn part1(input: &str) -> Result<u16> {
+fn part1(input: &str, layout: &PlaneLayout) -> Result<u16> {
     let mut max = 0;
 
     let ids = input
         .lines()
         .map(str::trim)
-        .map(|l| get_seat_row(l).map(seat_id));
+        .map(|l| layout.decode(l).map(|s| layout.seat_id(s)));
 
     for id in ids {
         max = max.max(id?);
@@ -60,20 +170,114 @@ fn part1(input: &str) -> Result<u16> {
     Ok(max)
 }
 
-fn part2(input: &str) -> Result<u16> {
-    let mut seats: Vec<_> = input
-        .lines()
-        .map(str::trim)
-        .map(|l| get_seat_row(l).map(seat_id))
-        .collect::<Result<_>>()?;
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum GapKind {
+    // Part of the run of missing seats at the very front or back of the plane.
+    FrontBlock,
+    BackBlock,
+    // Between occupied seats, but next to another missing seat.
+    Interior,
+    // Both neighbours are occupied, which is how the puzzle describes our seat.
+    Candidate,
+}
 
-    seats.sort_unstable();
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Gap {
+    id: u16,
+    row: u16,
+    seat: u16,
+    kind: GapKind,
+}
 
-    ArrWindows::new(&seats)
-        .filter(|[a, b]| b - a != 1)
-        .map(|[a, _]| a + 1)
-        .next()
-        .ok_or_else(|| eyre!("Seat not found"))
+// Whether each seat is occupied, indexed by seat ID.
+struct Cabin {
+    layout: PlaneLayout,
+    occupied: Vec<bool>,
+}
+
+impl Cabin {
+    fn parse(input: &str, layout: PlaneLayout) -> Result<Cabin> {
+        let mut occupied = vec![false; layout.rows() as usize * layout.columns() as usize];
+
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let id = layout.seat_id(layout.decode(line)?) as usize;
+            if std::mem::replace(&mut occupied[id], true) {
+                return Err(eyre!("Seat listed twice: {}", line));
+            }
+        }
+
+        Ok(Cabin { layout, occupied })
+    }
+
+    fn gaps(&self) -> Vec<Gap> {
+        let first = self.occupied.iter().position(|&o| o);
+        let last = self.occupied.iter().rposition(|&o| o);
+        let columns = self.layout.columns();
+
+        self.occupied
+            .iter()
+            .enumerate()
+            .filter(|(_, &occupied)| !occupied)
+            .map(|(id, _)| {
+                let kind = match (first, last) {
+                    (Some(first), _) if id < first => GapKind::FrontBlock,
+                    (_, Some(last)) if id > last => GapKind::BackBlock,
+                    // An empty plane is one big unavailable block.
+                    (None, _) => GapKind::FrontBlock,
+                    _ if self.occupied[id - 1] && self.occupied[id + 1] => GapKind::Candidate,
+                    _ => GapKind::Interior,
+                };
+
+                let id = id as u16;
+                Gap {
+                    id,
+                    row: id / columns,
+                    seat: id % columns,
+                    kind,
+                }
+            })
+            .collect()
+    }
+
+    fn find_seat(&self) -> Result<u16> {
+        let candidates: Vec<_> = self
+            .gaps()
+            .into_iter()
+            .filter(|g| g.kind == GapKind::Candidate)
+            .map(|g| g.id)
+            .collect();
+
+        match candidates[..] {
+            [id] => Ok(id),
+            [] => Err(eyre!("No free seat has both neighbours occupied")),
+            _ => Err(eyre!(
+                "Several free seats have both neighbours occupied: {}",
+                candidates.iter().join(", ")
+            )),
+        }
+    }
+}
+
+// One line per row, with `#` for occupied seats and `.` for free ones.
+impl Display for Cabin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let label_width = (self.layout.rows() - 1).to_string().len();
+
+        for (row, seats) in self
+            .occupied
+            .chunks(self.layout.columns() as usize)
+            .enumerate()
+        {
+            let seats: String = seats.iter().map(|&o| if o { '#' } else { '.' }).collect();
+            writeln!(f, "{:>width$} {}", row, seats, width = label_width)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn part2(input: &str, layout: &PlaneLayout) -> Result<u16> {
+    Cabin::parse(input, *layout)?.find_seat()
 }
 
 #[cfg(test)]
@@ -85,7 +289,7 @@ mod tests_2005 {
         let input = "FBFBBFFRLR";
         let expected = (44, 5);
 
-        assert_eq!(get_seat_row(input).unwrap(), expected);
+        assert_eq!(PlaneLayout::STANDARD.decode(input).unwrap(), expected);
     }
 
     #[test]
@@ -97,9 +301,143 @@ mod tests_2005 {
         ];
 
         for (i, (test, expected_seat, expected_id)) in tests.iter().enumerate() {
-            let seat_row = get_seat_row(test).unwrap();
+            let seat_row = PlaneLayout::STANDARD.decode(test).unwrap();
             assert_eq!(seat_row, *expected_seat, "{}", i);
-            assert_eq!(seat_id(seat_row), *expected_id, "{}", i);
+            assert_eq!(
+                PlaneLayout::STANDARD.seat_id(seat_row),
+                *expected_id,
+                "{}",
+                i
+            );
         }
     }
+
+    #[test]
+    fn decode_error_test() {
+        let layout = PlaneLayout::STANDARD;
+
+        assert!(layout.decode("FBFBBFFRL").is_err());
+        assert!(layout.decode("FBFBBFFRLRR").is_err());
+        assert!(layout.decode("FBFBBFRRLR").is_err());
+        assert!(layout.decode("FBFBBFFéR").is_err());
+        assert!(layout.decode("").is_err());
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let layouts = [
+            PlaneLayout::STANDARD,
+            PlaneLayout::new(1, 1, ('F', 'B'), ('L', 'R')).unwrap(),
+            PlaneLayout::new(5, 4, ('0', '1'), ('<', '>')).unwrap(),
+            PlaneLayout::new(10, 6, ('a', 'b'), ('a', 'b')).unwrap(),
+        ];
+
+        for layout in &layouts {
+            let mut ids = Vec::new();
+
+            for row in 0..layout.rows() {
+                for seat in 0..layout.columns() {
+                    let pass = layout.encode(row, seat).unwrap();
+                    assert_eq!(
+                        pass.chars().count() as u32,
+                        layout.row_bits + layout.col_bits
+                    );
+                    assert_eq!(
+                        layout.decode(&pass).unwrap(),
+                        (row, seat),
+                        "{:?} {}",
+                        layout,
+                        pass
+                    );
+                    ids.push(layout.seat_id((row, seat)));
+                }
+            }
+
+            // Seat IDs count up through the cabin without gaps.
+            let expected = 0..ids.len() as u32;
+            assert!(ids.iter().map(|&id| id as u32).eq(expected), "{:?}", layout);
+
+            assert!(layout.encode(layout.rows(), 0).is_err());
+            assert!(layout.encode(0, layout.columns()).is_err());
+        }
+
+        assert_eq!(PlaneLayout::STANDARD.encode(44, 5).unwrap(), "FBFBBFFRLR");
+    }
+
+    #[test]
+    fn layout_test() {
+        assert!(PlaneLayout::new(0, 3, ('F', 'B'), ('L', 'R')).is_err());
+        assert!(PlaneLayout::new(10, 7, ('F', 'B'), ('L', 'R')).is_err());
+        assert!(PlaneLayout::new(7, 3, ('F', 'F'), ('L', 'R')).is_err());
+
+        let layout = PlaneLayout::new(4, 2, ('F', 'B'), ('L', 'R')).unwrap();
+        assert_eq!(layout.seat_id(layout.decode("BFFBRL").unwrap()), 9 * 4 + 2);
+    }
+
+    fn small_cabin(ids: &[u16]) -> Cabin {
+        let layout = PlaneLayout::new(3, 2, ('F', 'B'), ('L', 'R')).unwrap();
+        let input = ids
+            .iter()
+            .map(|&id| layout.encode(id / 4, id % 4).unwrap())
+            .join("\n");
+
+        Cabin::parse(&input, layout).unwrap()
+    }
+
+    #[test]
+    fn gaps_test() {
+        let cabin = small_cabin(&[3, 4, 5, 7, 8, 11, 12, 14, 15, 16, 17, 18, 19, 20, 21]);
+
+        let expected = "0 ...#\n\
+                        1 ##.#\n\
+                        2 #..#\n\
+                        3 #.##\n\
+                        4 ####\n\
+                        5 ##..\n\
+                        6 ....\n\
+                        7 ....\n";
+        assert_eq!(cabin.to_string(), expected);
+
+        let gaps: Vec<_> = cabin.gaps().iter().map(|g| (g.id, g.kind)).collect();
+        let mut expected = vec![
+            (0, GapKind::FrontBlock),
+            (1, GapKind::FrontBlock),
+            (2, GapKind::FrontBlock),
+            (6, GapKind::Candidate),
+            (9, GapKind::Interior),
+            (10, GapKind::Interior),
+            (13, GapKind::Candidate),
+        ];
+        expected.extend((22..32).map(|id| (id, GapKind::BackBlock)));
+        assert_eq!(gaps, expected);
+
+        let gap = cabin.gaps()[3];
+        assert_eq!((gap.row, gap.seat), (1, 2));
+
+        assert!(cabin.find_seat().is_err());
+    }
+
+    #[test]
+    fn find_seat_test() {
+        assert_eq!(small_cabin(&[3, 4, 5, 7, 8, 9]).find_seat().unwrap(), 6);
+        assert!(small_cabin(&[3, 4, 5, 8, 9]).find_seat().is_err());
+        assert!(small_cabin(&[]).find_seat().is_err());
+        assert!(small_cabin(&[0, 1, 2]).find_seat().is_err());
+
+        let err = small_cabin(&[1, 3, 5]).find_seat().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Several free seats have both neighbours occupied: 2, 4"
+        );
+
+        let layout = PlaneLayout::STANDARD;
+        let input = (40..900)
+            .filter(|&id| id != 517)
+            .map(|id| layout.encode(id / 8, id % 8).unwrap())
+            .join("\n");
+        assert_eq!(part2(&input, &layout).unwrap(), 517);
+
+        let twice = "FBFBBFFRLR\nFBFBBFFRLR";
+        assert!(Cabin::parse(twice, layout).is_err());
+    }
 }
@@ -1,14 +1,23 @@
-use std::collections::HashSet;
+use std::ops::{BitAnd, BitOr};
 
 use aoc_lib::{Bench, BenchResult, Day};
-use color_eyre::eyre::Result;
+use color_eyre::{
+    eyre::{eyre, Result},
+    Report,
+};
+
+use super::common::split_records;
 
 pub const DAY: Day = Day {
     day: 6,
     name: "Custom Customs",
     part_1: run_part1,
     part_2: Some(run_part2),
-    other: &[],
+    other: &[
+        ("Majority", run_majority),
+        ("Exactly One", run_exactly_one),
+        ("Most Common", run_most_common),
+    ],
 };
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
@@ -19,48 +28,155 @@ fn run_part2(input: &str, b: Bench) -> BenchResult {
     b.bench(|| part2(input))
 }
 
-fn part1(input: &str) -> Result<usize> {
-    let groups = input.split("\n\n");
+fn run_majority(input: &str, b: Bench) -> BenchResult {
+    b.bench(|| sum_answers(input, Group::majority))
+}
+
+fn run_exactly_one(input: &str, b: Bench) -> BenchResult {
+    b.bench(|| sum_answers(input, |g| g.exactly(1)))
+}
+
+fn run_most_common(input: &str, b: Bench) -> BenchResult {
+    b.bench(|| {
+        let histogram = question_histogram(input)?;
+        let (count, question) = histogram
+            .iter()
+            .zip('a'..='z')
+            .max_by_key(|&(count, _)| count)
+            .unwrap();
+
+        Ok::<_, Report>(format!("{}: {}", question, count))
+    })
+}
+
+const QUESTIONS: usize = 26;
 
-    let mut sum = 0;
-    let mut buffer = HashSet::new();
+// One bit per question, with bit 0 for `a`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+struct Answers(u32);
 
-    for group in groups {
-        let answers = group.chars().filter(|c| c.is_alphabetic());
-        buffer.extend(answers);
+impl Answers {
+    const ALL: Answers = Answers((1 << QUESTIONS) - 1);
+
+    fn parse(person: &str) -> Result<Answers> {
+        person
+            .chars()
+            .try_fold(Answers::default(), |answers, c| match c {
+                'a'..='z' => Ok(answers | Answers(1 << (c as u32 - 'a' as u32))),
+                _ => Err(eyre!("Invalid answer: {}", c)),
+            })
+    }
 
-        sum += buffer.len();
-        buffer.clear();
+    fn contains(self, question: usize) -> bool {
+        self.0 & (1 << question) != 0
     }
 
-    Ok(sum)
+    fn count(self) -> usize {
+        self.0.count_ones() as usize
+    }
 }
 
-fn part2(input: &str) -> Result<usize> {
-    let groups = input.split("\n\n");
+impl BitOr for Answers {
+    type Output = Answers;
+
+    fn bitor(self, rhs: Answers) -> Answers {
+        Answers(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Answers {
+    type Output = Answers;
+
+    fn bitand(self, rhs: Answers) -> Answers {
+        Answers(self.0 & rhs.0)
+    }
+}
 
-    let mut sum = 0;
-    let mut group_buffer = HashSet::new();
-    let mut person_buffer = HashSet::new();
+// How many people in the group answered yes to each question.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+struct Group {
+    people: usize,
+    counts: [usize; QUESTIONS],
+    anyone: Answers,
+    everyone: Answers,
+}
 
-    for group in groups {
-        let mut people = group.lines().map(str::trim);
+impl Group {
+    fn parse(group: &str) -> Result<Group> {
+        let mut tally = Group {
+            everyone: Answers::ALL,
+            ..Group::default()
+        };
 
-        group_buffer.extend(people.next().unwrap().chars());
+        for person in group.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let answers = Answers::parse(person)?;
 
-        for person in people {
-            person_buffer.extend(person.chars());
+            tally.people += 1;
+            tally.anyone = tally.anyone | answers;
+            tally.everyone = tally.everyone & answers;
 
-            group_buffer.retain(|u| person_buffer.contains(u));
+            for (question, count) in tally.counts.iter_mut().enumerate() {
+                *count += answers.contains(question) as usize;
+            }
+        }
 
-            person_buffer.clear()
+        if tally.people == 0 {
+            tally.everyone = Answers::default();
         }
 
-        sum += group_buffer.len();
-        group_buffer.clear();
+        Ok(tally)
+    }
+
+    fn matching(&self, f: impl Fn(usize) -> bool) -> Answers {
+        let bits = self
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0 && f(count))
+            .fold(0, |bits, (question, _)| bits | 1 << question);
+
+        Answers(bits)
+    }
+
+    fn at_least(&self, people: usize) -> Answers {
+        self.matching(|count| count >= people)
+    }
+
+    fn exactly(&self, people: usize) -> Answers {
+        self.matching(|count| count == people)
+    }
+
+    // Answered yes by more than half the group.
+    fn majority(&self) -> Answers {
+        self.at_least(self.people / 2 + 1)
     }
+}
+
+fn parse_groups(input: &str) -> impl Iterator<Item = Result<Group>> + '_ {
+    split_records(input).map(Group::parse)
+}
+
+fn sum_answers(input: &str, query: impl Fn(&Group) -> Answers) -> Result<usize> {
+    parse_groups(input).try_fold(0, |sum, group| Ok(sum + query(&group?).count()))
+}
+
+// The total number of people who answered yes to each question, across every group.
+fn question_histogram(input: &str) -> Result<[usize; QUESTIONS]> {
+    parse_groups(input).try_fold([0; QUESTIONS], |mut histogram, group| {
+        for (total, count) in histogram.iter_mut().zip(&group?.counts) {
+            *total += count;
+        }
+
+        Ok(histogram)
+    })
+}
+
+fn part1(input: &str) -> Result<usize> {
+    sum_answers(input, |g| g.anyone)
+}
 
-    Ok(sum)
+fn part2(input: &str) -> Result<usize> {
+    sum_answers(input, |g| g.everyone)
 }
 
 #[cfg(test)]
@@ -88,4 +204,55 @@ mod tests_2006 {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn threshold_test() {
+        let group = Group::parse("abcx\nabcy\nabcz\nac\nb").unwrap();
+
+        assert_eq!(group.people, 5);
+        assert_eq!(group.anyone, Answers::parse("abcxyz").unwrap());
+        assert_eq!(group.everyone, Answers::default());
+        assert_eq!(group.at_least(4), Answers::parse("abc").unwrap());
+        assert_eq!(group.at_least(1), group.anyone);
+        assert_eq!(group.majority(), Answers::parse("abc").unwrap());
+        assert_eq!(group.exactly(1), Answers::parse("xyz").unwrap());
+        assert_eq!(group.exactly(5), group.everyone);
+        assert_eq!(group.at_least(0), group.anyone);
+
+        assert!(Group::parse("ab\na1").is_err());
+        assert!(Group::parse("aB").is_err());
+        let err = Group::parse("ab\naé").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid answer: é");
+
+        let empty = Group::parse("").unwrap();
+        assert_eq!(empty.anyone.count(), 0);
+        assert_eq!(empty.everyone.count(), 0);
+    }
+
+    #[test]
+    fn histogram_test() {
+        let input = aoc_lib::input(6).example(Example::Part1, 1).open().unwrap();
+
+        let mut expected = [0; QUESTIONS];
+        expected[..3].copy_from_slice(&[8, 4, 3]);
+        assert_eq!(question_histogram(&input).unwrap(), expected);
+
+        assert_eq!(sum_answers(&input, Group::majority).unwrap(), 6);
+        assert_eq!(sum_answers(&input, |g| g.exactly(1)).unwrap(), 9);
+    }
+
+    #[test]
+    fn line_endings_test() {
+        let input = aoc_lib::input(6).example(Example::Part1, 1).open().unwrap();
+        let crlf = input.replace('\n', "\r\n");
+        let padded = input.replace("\n\n", "\n  \t\n\n\n");
+
+        for input in &[crlf, padded, format!("\n\n{}\n\n", input)] {
+            assert_eq!(part1(input).unwrap(), 11, "{:?}", input);
+            assert_eq!(part2(input).unwrap(), 6, "{:?}", input);
+        }
+
+        assert_eq!(part2("").unwrap(), 0);
+        assert_eq!(part2(" \r\n\r\n").unwrap(), 0);
+    }
 }
@@ -1,100 +1,563 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
 
-use aoc_lib::{parsers::unsigned_number, Bench, BenchResult, Day, ParseResult, UserError};
+use aoc_lib::{parsers::unsigned_number, Bench, BenchResult, Day, NoError, ParseResult, UserError};
 use color_eyre::{
     eyre::{eyre, Result},
     Report,
 };
+use itertools::Itertools;
 use nom::{
     bytes::complete::{tag, take_until},
     error::ErrorKind,
     sequence::tuple,
 };
 
+use super::common::json_string;
+
 pub const DAY: Day = Day {
     day: 7,
     name: "Handy Haversacks",
     part_1: run_part1,
     part_2: Some(run_part2),
-    other: &[("Parse", run_parse)],
+    other: &[
+        ("Parse", run_parse),
+        ("Holder Paths", run_holder_paths),
+        ("Part 2 Big", run_part2_big),
+        ("Breakdown", run_breakdown),
+        ("Write DOT", run_write_dot),
+        ("Write JSON", run_write_json),
+    ],
 };
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
-    let rules = parse_bags(input).map_err(UserError)?;
-    b.bench(|| part1(&rules, "shiny gold"))
+    let graph = BagGraph::parse(input).map_err(UserError)?;
+    b.bench(|| part1(&graph, "shiny gold"))
 }
 
 fn run_part2(input: &str, b: Bench) -> BenchResult {
-    let rules = parse_bags(input).map_err(UserError)?;
-    b.bench(|| part2(&rules, "shiny gold"))
+    let graph = BagGraph::parse(input).map_err(UserError)?;
+    b.bench(|| part2(&graph, "shiny gold"))
+}
+
+fn run_part2_big(input: &str, b: Bench) -> BenchResult {
+    let graph = BagGraph::parse(input).map_err(UserError)?;
+    b.bench(|| count_contents::<BigCount>(&graph, "shiny gold"))
+}
+
+fn run_breakdown(input: &str, b: Bench) -> BenchResult {
+    let graph = BagGraph::parse(input).map_err(UserError)?;
+    b.bench(|| {
+        let breakdown = Breakdown::new(&graph, "shiny gold")?;
+        Ok::<_, Report>(format!("{} kinds of bag", breakdown.contents.len()))
+    })
+}
+
+fn run_write_dot(input: &str, b: Bench) -> BenchResult {
+    let graph = BagGraph::parse(input).map_err(UserError)?;
+    b.bench(|| graph.to_dot(Some("shiny gold")).map(|dot| dot.len()))
+}
+
+fn run_write_json(input: &str, b: Bench) -> BenchResult {
+    let graph = BagGraph::parse(input).map_err(UserError)?;
+    b.bench(|| Ok::<_, NoError>(graph.to_json().len()))
+}
+
+fn run_holder_paths(input: &str, b: Bench) -> BenchResult {
+    let graph = BagGraph::parse(input).map_err(UserError)?;
+    b.bench(|| {
+        let holders = graph.holders("shiny gold")?;
+        let deepest = holders.iter().map(|h| h.path.len() - 1).max().unwrap_or(0);
+        Ok::<_, Report>(format!("{} holders, up to {} deep", holders.len(), deepest))
+    })
 }
 
 fn run_parse(input: &str, b: Bench) -> BenchResult {
     b.bench(|| {
-        let data = parse_bags(input)?;
+        let data = BagGraph::parse(input)?;
         Ok::<_, Report>(ParseResult(data))
     })
 }
 
-fn parse_bags(input: &str) -> Result<HashMap<&str, HashMap<&str, usize>>> {
-    let mut bags = HashMap::new();
+fn parse_rule(line: &str) -> Result<(&str, Vec<(&str, usize)>)> {
+    let (contained, (this_bag, _)) =
+        tuple::<_, _, (&str, ErrorKind), _>((take_until(" bags"), tag(" bags contain ")))(line)
+            .map_err(|e| eyre!("Parse Error: {}", e))?;
+
+    if contained == "no other bags." {
+        return Ok((this_bag, Vec::new()));
+    }
+
+    let mut contents = Vec::new();
+    for sub_bag in contained.split_terminator(&[',', '.'][..]).map(str::trim) {
+        let (_, (count, sub_bag)) = tuple((unsigned_number, take_until("bag")))(sub_bag)
+            .map_err(|e| eyre!("Parse Error: {}", e))?;
+
+        contents.push((sub_bag.trim(), count?));
+    }
+
+    Ok((this_bag, contents))
+}
+
+type BagId = usize;
+
+// Each bag's name is interned to an ID, given in the order the bags are first mentioned.
+#[derive(Debug, Default)]
+struct BagGraph<'a> {
+    names: Vec<&'a str>,
+    ids: HashMap<&'a str, BagId>,
+    // The (bag, count) pairs each bag directly contains, and the reverse.
+    contents: Vec<Vec<(BagId, usize)>>,
+    containers: Vec<Vec<(BagId, usize)>>,
+}
+
+impl<'a> BagGraph<'a> {
+    fn parse(input: &'a str) -> Result<BagGraph<'a>> {
+        let mut graph = BagGraph::default();
+        let mut has_rule = Vec::new();
+
+        for line in input.lines().map(str::trim) {
+            let (bag, contents) = parse_rule(line)?;
+
+            let bag = graph.intern(bag);
+            has_rule.resize(graph.names.len(), false);
+            if std::mem::replace(&mut has_rule[bag], true) {
+                return Err(eyre!("Multiple rules for {} bags", graph.names[bag]));
+            }
+
+            for (sub_bag, count) in contents {
+                let sub_bag = graph.intern(sub_bag);
+                graph.contents[bag].push((sub_bag, count));
+                graph.containers[sub_bag].push((bag, count));
+            }
+        }
+
+        // A bag can't end up inside itself, so reject the rules outright if they loop.
+        graph.topological_order_from(0..graph.len())?;
+
+        Ok(graph)
+    }
+
+    fn intern(&mut self, name: &'a str) -> BagId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = self.names.len();
+        self.names.push(name);
+        self.ids.insert(name, id);
+        self.contents.push(Vec::new());
+        self.containers.push(Vec::new());
+
+        id
+    }
+
+    fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    fn id(&self, name: &str) -> Result<BagId> {
+        self.ids
+            .get(name)
+            .copied()
+            .ok_or_else(|| eyre!("Unknown bag: {}", name))
+    }
+
+    fn name(&self, id: BagId) -> &'a str {
+        self.names[id]
+    }
+
+    // Breadth-first search up through the containers. Returns every bag that can eventually
+    // hold `bag`, nearest first, along with the next bag down on each one's shortest path.
+    fn search_holders(&self, bag: BagId) -> (Vec<BagId>, Vec<Option<BagId>>) {
+        let mut next_down = vec![None; self.len()];
+        let mut holders = Vec::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(bag);
+
+        while let Some(cur) = queue.pop_front() {
+            for &(container, _) in &self.containers[cur] {
+                if next_down[container].is_none() {
+                    next_down[container] = Some(cur);
+                    holders.push(container);
+                    queue.push_back(container);
+                }
+            }
+        }
+
+        (holders, next_down)
+    }
+
+    fn holders(&self, bag: &str) -> Result<Vec<Holder>> {
+        let bag = self.id(bag)?;
+        let (holders, next_down) = self.search_holders(bag);
+
+        let holders = holders
+            .into_iter()
+            .map(|holder| {
+                let mut path = vec![holder];
+                while let Some(next) = next_down[*path.last().unwrap()] {
+                    path.push(next);
+                }
+
+                Holder { bag: holder, path }
+            })
+            .collect();
+
+        Ok(holders)
+    }
+
+    // Every bag that `bag` eventually holds, including itself.
+    fn reachable_from(&self, bag: BagId) -> Vec<bool> {
+        let mut reachable = vec![false; self.len()];
+        reachable[bag] = true;
+        let mut stack = vec![bag];
+
+        while let Some(cur) = stack.pop() {
+            for &(sub_bag, _) in &self.contents[cur] {
+                if !std::mem::replace(&mut reachable[sub_bag], true) {
+                    stack.push(sub_bag);
+                }
+            }
+        }
+
+        reachable
+    }
+
+    // Graphviz output, with an edge from each bag to each bag it holds labelled with how
+    // many. If `highlight` is given, that bag and everything inside it is drawn in red.
+    fn to_dot(&self, highlight: Option<&str>) -> Result<String> {
+        fn quote(name: &str) -> String {
+            format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+        }
+
+        let highlighted = match highlight {
+            Some(bag) => self.reachable_from(self.id(bag)?),
+            None => vec![false; self.len()],
+        };
+        const HIGHLIGHT: &str = "color=red penwidth=2";
 
-    for line in input.lines().map(str::trim) {
-        let (contained, (this_bag, _)) =
-            tuple::<_, _, (&str, ErrorKind), _>((take_until(" bags"), tag(" bags contain ")))(line)
-                .map_err(|e| eyre!("Parse Error: {}", e))?;
+        let mut dot = String::from("digraph bags {\n");
 
-        let bag_entry = bags.entry(this_bag).or_insert_with(HashMap::new);
+        for (bag, &name) in self.names.iter().enumerate() {
+            if highlighted[bag] {
+                dot.push_str(&format!("    {} [{}];\n", quote(name), HIGHLIGHT));
+            } else {
+                dot.push_str(&format!("    {};\n", quote(name)));
+            }
+        }
 
-        if contained == "no other bags." {
-            continue;
+        for (bag, contents) in self.contents.iter().enumerate() {
+            for &(sub_bag, count) in contents {
+                let style = if highlighted[bag] { HIGHLIGHT } else { "" };
+                dot.push_str(&format!(
+                    "    {} -> {} [{}];\n",
+                    quote(self.name(bag)),
+                    quote(self.name(sub_bag)),
+                    format!("label=\"{}\" {}", count, style).trim_end(),
+                ));
+            }
         }
 
-        for sub_bag in contained.split_terminator(&[',', '.'][..]).map(str::trim) {
-            let (_, (count, sub_bag)) = tuple((unsigned_number, take_until("bag")))(sub_bag)
-                .map_err(|e| eyre!("Parse Error: {}", e))?;
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+
+    // An object mapping each bag to what it directly holds, one bag per line.
+    fn to_json(&self) -> String {
+        let bags = self
+            .names
+            .iter()
+            .zip(&self.contents)
+            .map(|(&name, contents)| {
+                let contents = contents
+                    .iter()
+                    .map(|&(sub_bag, count)| {
+                        format!(
+                            "{{\"bag\": {}, \"count\": {}}}",
+                            json_string(self.name(sub_bag)),
+                            count
+                        )
+                    })
+                    .join(", ");
+                format!("  {}: [{}]", json_string(name), contents)
+            })
+            .join(",\n");
+
+        if bags.is_empty() {
+            "{}\n".to_owned()
+        } else {
+            format!("{{\n{}\n}}\n", bags)
+        }
+    }
+
+    // Orders every bag reachable from `roots` so that each comes before anything it contains.
+    // Walks the graph with an explicit stack, so deep rule sets can't overflow.
+    fn topological_order_from(&self, roots: impl IntoIterator<Item = BagId>) -> Result<Vec<BagId>> {
+        #[derive(Copy, Clone, PartialEq, Eq)]
+        enum State {
+            New,
+            Open,
+            Done,
+        }
 
-            bag_entry.insert(sub_bag.trim(), count?);
+        let mut state = vec![State::New; self.len()];
+        let mut post_order = Vec::with_capacity(self.len());
+        // Each open bag, and the index of the next of its contents to visit.
+        let mut stack: Vec<(BagId, usize)> = Vec::new();
+
+        for root in roots {
+            if state[root] != State::New {
+                continue;
+            }
+            state[root] = State::Open;
+            stack.push((root, 0));
+
+            while let Some((bag, next)) = stack.last_mut() {
+                let bag = *bag;
+                let child = match self.contents[bag].get(*next) {
+                    Some(&(child, _)) => child,
+                    None => {
+                        state[bag] = State::Done;
+                        post_order.push(bag);
+                        stack.pop();
+                        continue;
+                    }
+                };
+                *next += 1;
+
+                match state[child] {
+                    State::New => {
+                        state[child] = State::Open;
+                        stack.push((child, 0));
+                    }
+                    State::Open => {
+                        let start = stack.iter().position(|&(b, _)| b == child).unwrap();
+                        let cycle = stack[start..]
+                            .iter()
+                            .map(|&(b, _)| self.name(b))
+                            .chain(Some(self.name(child)))
+                            .join(" -> ");
+
+                        return Err(eyre!("Bag rules contain a cycle: {}", cycle));
+                    }
+                    State::Done => {}
+                }
+            }
         }
+
+        post_order.reverse();
+        Ok(post_order)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Holder {
+    bag: BagId,
+    // From the holder down to the bag being held, inclusive.
+    path: Vec<BagId>,
+}
+
+fn part1(graph: &BagGraph, bag: &str) -> Result<usize> {
+    let (holders, _) = graph.search_holders(graph.id(bag)?);
+    Ok(holders.len())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CountOverflow {
+    bag: String,
+}
+
+impl Display for CountOverflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Bag count overflowed at {} bags", self.bag)
+    }
+}
+
+impl Error for CountOverflow {}
+
+// A number of bags.
+trait Tally: Sized + Clone {
+    fn zero() -> Self;
+    fn one() -> Self;
+    // Returns `self + count * other`, or None if it doesn't fit.
+    fn add_product(&self, count: usize, other: &Self) -> Option<Self>;
+}
+
+impl Tally for u64 {
+    fn zero() -> u64 {
+        0
     }
 
-    Ok(bags)
+    fn one() -> u64 {
+        1
+    }
+
+    fn add_product(&self, count: usize, other: &u64) -> Option<u64> {
+        other.checked_mul(count as u64)?.checked_add(*self)
+    }
+}
+
+// Just enough of an arbitrary-precision integer to count bags. Stored as little-endian
+// base 10^9 digits, which keeps printing simple.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BigCount(Vec<u32>);
+
+impl BigCount {
+    const BASE: u128 = 1_000_000_000;
 }
 
-fn recursive_search<'a>(
-    bag_rules: &HashMap<&'a str, HashMap<&'a str, usize>>,
-    bag: &str,
-    seen: &mut HashSet<&'a str>,
-) {
-    for (cur_bag, contains) in bag_rules {
-        if contains.contains_key(bag) && seen.insert(*cur_bag) {
-            // If we've already seen the cur_bag, there's no reason to continue
-            // up the chain.
-            recursive_search(bag_rules, cur_bag, seen)
+impl Tally for BigCount {
+    fn zero() -> BigCount {
+        BigCount(Vec::new())
+    }
+
+    fn one() -> BigCount {
+        BigCount(vec![1])
+    }
+
+    fn add_product(&self, count: usize, other: &BigCount) -> Option<BigCount> {
+        let mut digits = self.0.clone();
+        digits.resize(digits.len().max(other.0.len()), 0);
+
+        let mut carry = 0;
+        for (i, digit) in digits.iter_mut().enumerate() {
+            let product = other.0.get(i).map_or(0, |&d| d as u128 * count as u128);
+            let sum = *digit as u128 + product + carry;
+            *digit = (sum % Self::BASE) as u32;
+            carry = sum / Self::BASE;
+        }
+
+        while carry > 0 {
+            digits.push((carry % Self::BASE) as u32);
+            carry /= Self::BASE;
+        }
+
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        Some(BigCount(digits))
+    }
+}
+
+impl Display for BigCount {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut digits = self.0.iter().rev();
+
+        match digits.next() {
+            Some(first) => write!(f, "{}", first)?,
+            None => return write!(f, "0"),
+        }
+
+        for digit in digits {
+            write!(f, "{:09}", digit)?;
+        }
+
+        Ok(())
+    }
+}
+
+// How many bags each bag reachable from `start` amounts to, counting itself. Bags that
+// can't be reached are left as None.
+fn bag_totals<T: Tally>(graph: &BagGraph, start: BagId) -> Result<Vec<Option<T>>> {
+    let order = graph.topological_order_from(Some(start))?;
+    let mut totals = vec![None; graph.len()];
+
+    for &bag in order.iter().rev() {
+        let mut total = T::one();
+        for &(sub_bag, count) in &graph.contents[bag] {
+            // Contents come later in the order, so they've already been counted.
+            let sub_total = totals[sub_bag].as_ref().unwrap();
+            total = total
+                .add_product(count, sub_total)
+                .ok_or_else(|| CountOverflow {
+                    bag: graph.name(bag).to_owned(),
+                })?;
         }
+
+        totals[bag] = Some(total);
     }
+
+    Ok(totals)
 }
 
-fn part1(bag_rules: &HashMap<&str, HashMap<&str, usize>>, bag: &str) -> Result<usize> {
-    let mut known_bags = HashSet::new();
+fn count_contents<T: Tally>(graph: &BagGraph, bag: &str) -> Result<T> {
+    let start = graph.id(bag)?;
+    let totals = bag_totals::<T>(graph, start)?;
+
+    let mut count = T::zero();
+    for &(sub_bag, sub_count) in &graph.contents[start] {
+        let sub_total = totals[sub_bag].as_ref().unwrap();
+        count = count
+            .add_product(sub_count, sub_total)
+            .ok_or_else(|| CountOverflow {
+                bag: bag.to_owned(),
+            })?;
+    }
 
-    recursive_search(bag_rules, bag, &mut known_bags);
+    Ok(count)
+}
 
-    Ok(known_bags.len())
+// How many of each kind of bag end up inside a single `bag`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Breakdown<'a> {
+    bag: &'a str,
+    contents: Vec<(&'a str, u64)>,
 }
 
-fn recursive_count(bag_rules: &HashMap<&str, HashMap<&str, usize>>, bag: &str) -> usize {
-    let mut count = 1;
+impl<'a> Breakdown<'a> {
+    fn new(graph: &BagGraph<'a>, bag: &str) -> Result<Breakdown<'a>> {
+        let start = graph.id(bag)?;
+        let order = graph.topological_order_from(Some(start))?;
+
+        let mut copies = vec![0u64; graph.len()];
+        copies[start] = 1;
+
+        // Every container comes before its contents, so its copies are final by the time
+        // we hand them down.
+        for &bag in &order {
+            for &(sub_bag, count) in &graph.contents[bag] {
+                copies[sub_bag] = copies[sub_bag]
+                    .add_product(count, &copies[bag])
+                    .ok_or_else(|| CountOverflow {
+                        bag: graph.name(sub_bag).to_owned(),
+                    })?;
+            }
+        }
 
-    for (sub_bag, sub_count) in &bag_rules[bag] {
-        count += sub_count * recursive_count(bag_rules, sub_bag);
+        Ok(Breakdown {
+            bag: graph.name(start),
+            contents: order[1..]
+                .iter()
+                .map(|&b| (graph.name(b), copies[b]))
+                .collect(),
+        })
     }
+}
 
-    count
+impl Display for Breakdown<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "1 {} holds ", self.bag)?;
+
+        if self.contents.is_empty() {
+            return write!(f, "nothing");
+        }
+
+        let contents = self
+            .contents
+            .iter()
+            .map(|(bag, count)| format!("{} {}", count, bag))
+            .join(", ");
+        write!(f, "{}", contents)
+    }
 }
 
-fn part2(bag_rules: &HashMap<&str, HashMap<&str, usize>>, bag: &str) -> Result<usize> {
-    Ok(recursive_count(bag_rules, bag) - 1)
+fn part2(graph: &BagGraph, bag: &str) -> Result<u64> {
+    count_contents(graph, bag)
 }
 
 #[cfg(test)]
@@ -103,6 +566,18 @@ mod tests_2007 {
     use aoc_lib::Example;
     use maplit::hashmap;
 
+    fn rules<'a>(graph: &BagGraph<'a>) -> HashMap<&'a str, HashMap<&'a str, usize>> {
+        (0..graph.len())
+            .map(|bag| {
+                let contents = graph.contents[bag]
+                    .iter()
+                    .map(|&(sub_bag, count)| (graph.name(sub_bag), count))
+                    .collect();
+                (graph.name(bag), contents)
+            })
+            .collect()
+    }
+
     #[test]
     fn parse_test() {
         let input = aoc_lib::input(7).example(Example::Part1, 1).open().unwrap();
@@ -139,7 +614,7 @@ mod tests_2007 {
             "dotted black" => HashMap::new(),
         };
 
-        let actual = parse_bags(&input).unwrap();
+        let actual = rules(&BagGraph::parse(&input).unwrap());
 
         assert_eq!(expected, actual);
     }
@@ -147,7 +622,7 @@ mod tests_2007 {
     #[test]
     fn part1_example() {
         let input = aoc_lib::input(7).example(Example::Part1, 1).open().unwrap();
-        let bags = parse_bags(&input).unwrap();
+        let bags = BagGraph::parse(&input).unwrap();
 
         let expected = 4;
         let actual = part1(&bags, "shiny gold").unwrap();
@@ -158,7 +633,7 @@ mod tests_2007 {
     #[test]
     fn part2_example1() {
         let input = aoc_lib::input(7).example(Example::Part1, 1).open().unwrap();
-        let bags = parse_bags(&input).unwrap();
+        let bags = BagGraph::parse(&input).unwrap();
 
         let expected = 32;
         let actual = part2(&bags, "shiny gold").unwrap();
@@ -169,11 +644,282 @@ mod tests_2007 {
     #[test]
     fn part2_example2() {
         let input = aoc_lib::input(7).example(Example::Part2, 1).open().unwrap();
-        let bags = parse_bags(&input).unwrap();
+        let bags = BagGraph::parse(&input).unwrap();
 
         let expected = 126;
         let actual = part2(&bags, "shiny gold").unwrap();
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn graph_test() {
+        let input = aoc_lib::input(7).example(Example::Part1, 1).open().unwrap();
+        let graph = BagGraph::parse(&input).unwrap();
+
+        assert_eq!(graph.len(), 9);
+        assert_eq!(graph.name(graph.id("shiny gold").unwrap()), "shiny gold");
+        assert!(graph.id("plaid purple").is_err());
+        assert!(part1(&graph, "plaid purple").is_err());
+        assert!(part2(&graph, "plaid purple").is_err());
+
+        let order = graph.topological_order_from(0..graph.len()).unwrap();
+        assert_eq!(order.len(), graph.len());
+        for bag in 0..graph.len() {
+            let pos = order.iter().position(|&b| b == bag).unwrap();
+            for &(sub_bag, _) in &graph.contents[bag] {
+                assert!(order[..pos].iter().all(|&b| b != sub_bag));
+            }
+            for &(container, _) in &graph.containers[bag] {
+                assert!(graph.contents[container].iter().any(|&(b, _)| b == bag));
+            }
+        }
+
+        let order = graph
+            .topological_order_from(Some(graph.id("shiny gold").unwrap()))
+            .unwrap();
+        let names: Vec<_> = order.iter().map(|&b| graph.name(b)).collect();
+        assert_eq!(
+            names,
+            [
+                "shiny gold",
+                "vibrant plum",
+                "dark olive",
+                "dotted black",
+                "faded blue"
+            ]
+        );
+    }
+
+    #[test]
+    fn cycle_test() {
+        let tests = [
+            (
+                "shiny gold bags contain 1 dark red bag, 2 faded blue bags.
+                 dark red bags contain 2 dark orange bags.
+                 dark orange bags contain 1 shiny gold bag.
+                 faded blue bags contain no other bags.",
+                "shiny gold -> dark red -> dark orange -> shiny gold",
+            ),
+            (
+                "shiny gold bags contain 1 shiny gold bag.",
+                "shiny gold -> shiny gold",
+            ),
+            // The loop doesn't need to involve the bags either part asks about.
+            (
+                "shiny gold bags contain 1 faded blue bag.
+                 faded blue bags contain no other bags.
+                 light red bags contain 1 dark red bag.
+                 dark red bags contain 2 light red bags.",
+                "light red -> dark red -> light red",
+            ),
+        ];
+
+        for (input, cycle) in &tests {
+            let err = BagGraph::parse(input).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("Bag rules contain a cycle: {}", cycle)
+            );
+        }
+
+        let input = "shiny gold bags contain no other bags.
+                     shiny gold bags contain 1 dark red bag.";
+        assert!(BagGraph::parse(input).is_err());
+    }
+
+    #[test]
+    fn holders_test() {
+        let input = aoc_lib::input(7).example(Example::Part1, 1).open().unwrap();
+        let graph = BagGraph::parse(&input).unwrap();
+
+        let paths = |bag| -> Vec<String> {
+            graph
+                .holders(bag)
+                .unwrap()
+                .iter()
+                .map(|h| {
+                    assert_eq!(h.path[0], h.bag);
+                    h.path.iter().map(|&b| graph.name(b)).join(" -> ")
+                })
+                .collect()
+        };
+
+        let expected = [
+            "bright white -> shiny gold",
+            "muted yellow -> shiny gold",
+            "light red -> bright white -> shiny gold",
+            "dark orange -> bright white -> shiny gold",
+        ];
+        assert_eq!(paths("shiny gold"), expected);
+
+        let expected = [
+            "muted yellow -> faded blue",
+            "dark olive -> faded blue",
+            "vibrant plum -> faded blue",
+            "light red -> muted yellow -> faded blue",
+            "dark orange -> muted yellow -> faded blue",
+            "shiny gold -> dark olive -> faded blue",
+            "bright white -> shiny gold -> dark olive -> faded blue",
+        ];
+        assert_eq!(paths("faded blue"), expected);
+        assert_eq!(part1(&graph, "faded blue").unwrap(), 7);
+
+        assert!(paths("light red").is_empty());
+        assert!(graph.holders("plaid purple").is_err());
+    }
+
+    // Each bag in the chain holds `count` of the next.
+    fn chain(len: usize, count: usize) -> String {
+        (0..len)
+            .map(|i| {
+                format!(
+                    "shade{} tone bags contain {} shade{} tone bags.\n",
+                    i,
+                    count,
+                    i + 1
+                )
+            })
+            .chain(Some(format!(
+                "shade{} tone bags contain no other bags.",
+                len
+            )))
+            .collect()
+    }
+
+    #[test]
+    fn overflow_test() {
+        let input = chain(80, 3);
+        let graph = BagGraph::parse(&input).unwrap();
+
+        let err = part2(&graph, "shade0 tone").unwrap_err();
+        let overflow = err.downcast_ref::<CountOverflow>().unwrap();
+        assert_eq!(overflow.bag, "shade39 tone");
+
+        // The sum of 3^i for i from 1 to 80.
+        let expected = "221713244121518884974124815309574946400";
+        let actual = count_contents::<BigCount>(&graph, "shade0 tone").unwrap();
+        assert_eq!(actual.to_string(), expected);
+
+        // Both ways of counting agree while it fits.
+        assert_eq!(part2(&graph, "shade40 tone").unwrap(), 18236498188585393200);
+        let big = count_contents::<BigCount>(&graph, "shade40 tone").unwrap();
+        assert_eq!(big.to_string(), "18236498188585393200");
+
+        assert!(Breakdown::new(&graph, "shade0 tone").is_err());
+    }
+
+    #[test]
+    fn big_count_test() {
+        let input = aoc_lib::input(7).example(Example::Part2, 1).open().unwrap();
+        let graph = BagGraph::parse(&input).unwrap();
+
+        let actual = count_contents::<BigCount>(&graph, "shiny gold").unwrap();
+        assert_eq!(actual.to_string(), "126");
+
+        let actual = count_contents::<BigCount>(&graph, "dark violet").unwrap();
+        assert_eq!(actual.to_string(), "0");
+
+        let big = BigCount::one().add_product(usize::MAX, &BigCount::one());
+        assert_eq!(
+            big.unwrap().to_string(),
+            (usize::MAX as u128 + 1).to_string()
+        );
+
+        let billion = BigCount::zero().add_product(1_000_000_000, &BigCount::one());
+        assert_eq!(billion.unwrap().to_string(), "1000000000");
+    }
+
+    #[test]
+    fn breakdown_test() {
+        let input = aoc_lib::input(7).example(Example::Part2, 1).open().unwrap();
+        let graph = BagGraph::parse(&input).unwrap();
+
+        let breakdown = Breakdown::new(&graph, "shiny gold").unwrap();
+        assert_eq!(
+            breakdown.to_string(),
+            "1 shiny gold holds 2 dark red, 4 dark orange, 8 dark yellow, 16 dark green, \
+             32 dark blue, 64 dark violet"
+        );
+
+        let input = aoc_lib::input(7).example(Example::Part1, 1).open().unwrap();
+        let graph = BagGraph::parse(&input).unwrap();
+
+        let breakdown = Breakdown::new(&graph, "shiny gold").unwrap();
+        let total: u64 = breakdown.contents.iter().map(|&(_, count)| count).sum();
+        assert_eq!(total, part2(&graph, "shiny gold").unwrap());
+        assert_eq!(
+            breakdown.to_string(),
+            "1 shiny gold holds 2 vibrant plum, 1 dark olive, 16 dotted black, 13 faded blue"
+        );
+
+        let breakdown = Breakdown::new(&graph, "faded blue").unwrap();
+        assert_eq!(breakdown.to_string(), "1 faded blue holds nothing");
+    }
+
+    #[test]
+    fn dot_test() {
+        let input = aoc_lib::input(7).example(Example::Part1, 1).open().unwrap();
+        let graph = BagGraph::parse(&input).unwrap();
+
+        let expected = r#"digraph bags {
+    "light red";
+    "bright white";
+    "muted yellow";
+    "dark orange";
+    "shiny gold" [color=red penwidth=2];
+    "faded blue" [color=red penwidth=2];
+    "dark olive" [color=red penwidth=2];
+    "vibrant plum" [color=red penwidth=2];
+    "dotted black" [color=red penwidth=2];
+    "light red" -> "bright white" [label="1"];
+    "light red" -> "muted yellow" [label="2"];
+    "bright white" -> "shiny gold" [label="1"];
+    "muted yellow" -> "shiny gold" [label="2"];
+    "muted yellow" -> "faded blue" [label="9"];
+    "dark orange" -> "bright white" [label="3"];
+    "dark orange" -> "muted yellow" [label="4"];
+    "shiny gold" -> "dark olive" [label="1" color=red penwidth=2];
+    "shiny gold" -> "vibrant plum" [label="2" color=red penwidth=2];
+    "dark olive" -> "faded blue" [label="3" color=red penwidth=2];
+    "dark olive" -> "dotted black" [label="4" color=red penwidth=2];
+    "vibrant plum" -> "faded blue" [label="5" color=red penwidth=2];
+    "vibrant plum" -> "dotted black" [label="6" color=red penwidth=2];
+}
+"#;
+        assert_eq!(graph.to_dot(Some("shiny gold")).unwrap(), expected);
+
+        let plain = graph.to_dot(None).unwrap();
+        assert!(!plain.contains("color=red"));
+        assert_eq!(plain.lines().count(), expected.lines().count());
+
+        assert!(graph.to_dot(Some("plaid purple")).is_err());
+
+        let graph = BagGraph::parse(r#"odd "quoted\ bags contain no other bags."#).unwrap();
+        assert_eq!(
+            graph.to_dot(None).unwrap(),
+            "digraph bags {\n    \"odd \\\"quoted\\\\\";\n}\n"
+        );
+    }
+
+    #[test]
+    fn json_test() {
+        let input = aoc_lib::input(7).example(Example::Part2, 1).open().unwrap();
+        let graph = BagGraph::parse(&input).unwrap();
+
+        let expected = [
+            "{",
+            r#"  "shiny gold": [{"bag": "dark red", "count": 2}],"#,
+            r#"  "dark red": [{"bag": "dark orange", "count": 2}],"#,
+            r#"  "dark orange": [{"bag": "dark yellow", "count": 2}],"#,
+            r#"  "dark yellow": [{"bag": "dark green", "count": 2}],"#,
+            r#"  "dark green": [{"bag": "dark blue", "count": 2}],"#,
+            r#"  "dark blue": [{"bag": "dark violet", "count": 2}],"#,
+            r#"  "dark violet": []"#,
+            "}",
+        ];
+        assert_eq!(graph.to_json().lines().collect::<Vec<_>>(), expected);
+
+        assert_eq!(BagGraph::default().to_json(), "{}\n");
+    }
 }
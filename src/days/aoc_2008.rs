@@ -1,4 +1,8 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
 
 use aoc_lib::{parsers::split_pair, Bench, BenchResult, Day, ParseResult, UserError};
 use color_eyre::{
@@ -11,7 +15,7 @@ pub const DAY: Day = Day {
     name: "Handheld Halting",
     part_1: run_part1,
     part_2: Some(run_part2),
-    other: &[("Parse", run_parse)],
+    other: &[("Parse", run_parse), ("Round Trip", run_round_trip)],
 };
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
@@ -28,6 +32,20 @@ fn run_part2(input: &str, b: Bench) -> BenchResult {
     b.bench(|| part2(&instrs))
 }
 
+fn run_round_trip(input: &str, b: Bench) -> BenchResult {
+    let instrs = Instruction::parse(input).map_err(UserError)?;
+    b.bench(|| {
+        let source = disassemble(&instrs, true);
+        let assembled = assemble(&source)?;
+
+        if assembled != instrs {
+            return Err(eyre!("Program changed after round trip"));
+        }
+
+        Ok::<_, Report>(source.len())
+    })
+}
+
 fn run_parse(input: &str, b: Bench) -> BenchResult {
     b.bench(|| {
         let data = Instruction::parse(input)?;
@@ -37,6 +55,19 @@ fn run_parse(input: &str, b: Bench) -> BenchResult {
 
 const VALID_VAL_START: &[char] = &['-', '+'];
 
+// Values must have a sign. Returns the value ready to be parsed as a number.
+fn signed_value(val: &str) -> Option<&str> {
+    if val.starts_with(VALID_VAL_START) {
+        let (sign, mag) = val.split_at(1);
+        match sign {
+            "-" => Some(val),
+            _ => Some(mag),
+        }
+    } else {
+        None
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum Instruction {
     Acc(i64),
@@ -51,15 +82,7 @@ impl Instruction {
         for line in program.lines().map(str::trim) {
             let (op, val) = split_pair(line, " ")?;
 
-            let val = if val.starts_with(VALID_VAL_START) {
-                let (sign, mag) = val.split_at(1);
-                match sign {
-                    "-" => val,
-                    _ => mag,
-                }
-            } else {
-                return Err(eyre!("Invalid op value: `{}`", line));
-            };
+            let val = signed_value(val).ok_or_else(|| eyre!("Invalid op value: `{}`", line))?;
 
             let instr = match op {
                 "acc" => Instruction::Acc(val.parse()?),
@@ -83,6 +106,129 @@ impl Instruction {
     }
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Acc(val) => write!(f, "acc {:+}", val),
+            Instruction::Jmp(val) => write!(f, "jmp {:+}", val),
+            Instruction::Nop(val) => write!(f, "nop {:+}", val),
+        }
+    }
+}
+
+// Writes the program back out, one instruction per line. With `labels`, every jump that
+// lands inside the program (or just past the end) jumps to a label instead of an offset.
+fn disassemble(program: &[Instruction], labels: bool) -> String {
+    let target = |addr: usize, offset: isize| {
+        Some(addr as isize + offset)
+            .filter(|target| (0..=program.len() as isize).contains(target))
+            .map(|target| target as usize)
+    };
+
+    let mut is_target = vec![false; program.len() + 1];
+    if labels {
+        for (addr, instr) in program.iter().enumerate() {
+            if let Instruction::Jmp(offset) = instr {
+                if let Some(target) = target(addr, *offset) {
+                    is_target[target] = true;
+                }
+            }
+        }
+    }
+
+    let mut source = String::new();
+    for (addr, instr) in program.iter().enumerate() {
+        if is_target[addr] {
+            source.push_str(&format!("L{}:\n", addr));
+        }
+
+        let indent = if labels { "    " } else { "" };
+        match instr {
+            Instruction::Jmp(offset) if labels => match target(addr, *offset) {
+                Some(target) => source.push_str(&format!("{}jmp L{}\n", indent, target)),
+                None => source.push_str(&format!("{}{}\n", indent, instr)),
+            },
+            _ => source.push_str(&format!("{}{}\n", indent, instr)),
+        }
+    }
+
+    if is_target[program.len()] {
+        source.push_str(&format!("L{}:\n", program.len()));
+    }
+
+    source
+}
+
+// Like `Instruction::parse`, but allows blank lines, `;` comments, and labels. A label is
+// defined with `name:`, either on its own line or before an instruction, and can be used
+// in place of the offset for `jmp` and `nop`.
+fn assemble(source: &str) -> Result<Vec<Instruction>> {
+    fn is_label(name: &str) -> bool {
+        let mut chars = name.chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }
+
+    // First pass: find where each label points, and strip the source down to instructions.
+    let mut labels = HashMap::new();
+    let mut lines = Vec::new();
+
+    for (line_no, line) in source.lines().enumerate().map(|(i, l)| (i + 1, l)) {
+        let mut line = line.split(';').next().unwrap_or_default().trim();
+
+        if let Some((label, rest)) = line.split_once(':') {
+            let label = label.trim();
+            if !is_label(label) {
+                return Err(eyre!("Line {}: invalid label `{}`", line_no, label));
+            }
+            if labels.insert(label, lines.len()).is_some() {
+                return Err(eyre!("Line {}: label `{}` defined twice", line_no, label));
+            }
+
+            line = rest.trim();
+        }
+
+        if !line.is_empty() {
+            lines.push((line_no, line));
+        }
+    }
+
+    // Second pass: resolve labels to offsets from each instruction.
+    let mut program = Vec::with_capacity(lines.len());
+
+    for (addr, &(line_no, line)) in lines.iter().enumerate() {
+        let (op, val) = line
+            .split_once(char::is_whitespace)
+            .map(|(op, val)| (op, val.trim()))
+            .ok_or_else(|| eyre!("Line {}: missing value in `{}`", line_no, line))?;
+
+        let offset = |val: &str| -> Result<isize> {
+            match labels.get(val) {
+                Some(&target) => Ok(target as isize - addr as isize),
+                None if is_label(val) => Err(eyre!("Line {}: unknown label `{}`", line_no, val)),
+                None => parse_value(val, line_no),
+            }
+        };
+
+        let instr = match op {
+            "acc" => Instruction::Acc(parse_value(val, line_no)?),
+            "jmp" => Instruction::Jmp(offset(val)?),
+            "nop" => Instruction::Nop(offset(val)?),
+            _ => return Err(eyre!("Line {}: invalid opcode `{}`", line_no, op)),
+        };
+
+        program.push(instr);
+    }
+
+    Ok(program)
+}
+
+fn parse_value<T: FromStr>(val: &str, line_no: usize) -> Result<T> {
+    signed_value(val)
+        .and_then(|v| v.parse().ok())
+        .ok_or_else(|| eyre!("Line {}: invalid value `{}`", line_no, val))
+}
+
 #[derive(Debug, Default)]
 struct Computer {
     acc: i64,
@@ -193,4 +339,96 @@ mod tests_2008 {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn disassemble_test() {
+        let input = aoc_lib::input(8).example(Example::Part1, 1).open().unwrap();
+        let instrs = Instruction::parse(&input).unwrap();
+
+        let plain = disassemble(&instrs, false);
+        assert_eq!(
+            plain.lines().collect::<Vec<_>>(),
+            input.lines().map(str::trim).collect::<Vec<_>>()
+        );
+
+        let expected = "    nop +0
+L1:
+    acc +1
+    jmp L6
+L3:
+    acc +3
+    jmp L1
+    acc -99
+L6:
+    acc +1
+    jmp L3
+    acc +6
+";
+        assert_eq!(disassemble(&instrs, true), expected);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        use Instruction::*;
+
+        let programs = [
+            Instruction::parse(&aoc_lib::input(8).example(Example::Parse, 1).open().unwrap())
+                .unwrap(),
+            Instruction::parse(&aoc_lib::input(8).example(Example::Part1, 1).open().unwrap())
+                .unwrap(),
+            // Jumps to itself, off either end, and just past the end.
+            vec![Jmp(0), Jmp(-5), Acc(-3), Jmp(10), Nop(-2), Jmp(1)],
+            vec![],
+        ];
+
+        for program in &programs {
+            for &labels in &[false, true] {
+                let source = disassemble(program, labels);
+                assert_eq!(assemble(&source).unwrap(), *program, "{}", source);
+            }
+        }
+
+        let source = disassemble(&programs[2], true);
+        let expected = "L0:
+    jmp L0
+    jmp -5
+    acc -3
+    jmp +10
+    nop -2
+    jmp L6
+L6:
+";
+        assert_eq!(source, expected);
+    }
+
+    #[test]
+    fn assemble_test() {
+        use Instruction::*;
+
+        let source = "
+            ; Count up forever.
+            start:
+                acc +1      ; bump it
+            loop: nop end
+                jmp start
+
+            end:
+        ";
+        assert_eq!(assemble(source).unwrap(), [Acc(1), Nop(2), Jmp(-2)]);
+
+        let errors = [
+            ("jmp nowhere", "Line 1: unknown label `nowhere`"),
+            ("a:\na:", "Line 2: label `a` defined twice"),
+            ("acc a\na:", "Line 1: invalid value `a`"),
+            ("acc 5", "Line 1: invalid value `5`"),
+            ("mul +2", "Line 1: invalid opcode `mul`"),
+            ("2bad: nop +0", "Line 1: invalid label `2bad`"),
+            ("\nnop", "Line 2: missing value in `nop`"),
+        ];
+
+        for (source, expected) in &errors {
+            let err = assemble(source).unwrap_err();
+            assert_eq!(err.to_string(), *expected, "{:?}", source);
+        }
+    }
 }
@@ -18,6 +18,8 @@ use nom::{
     sequence::tuple,
 };
 
+use super::common::eliminate_candidates;
+
 pub const DAY: Day = Day {
     day: 16,
     name: "Ticket Translation",
@@ -155,35 +157,7 @@ fn part2(data: &TicketData) -> Result<u64> {
             });
     }
 
-    loop {
-        let mut did_advance = false;
-
-        // If we've narrowed one field down to a single possibility, remove it from the others.
-        for i in 0..field_tracker.len() {
-            if field_tracker[i].len() != 1 {
-                continue;
-            }
-            let entry = *field_tracker[i].iter().next().unwrap();
-
-            field_tracker
-                .iter_mut()
-                .enumerate()
-                .filter(|(j, _)| *j != i)
-                .for_each(|(_, field)| did_advance |= field.remove(&entry));
-        }
-
-        if !did_advance {
-            return Err(eyre!("Didn't advance filter"));
-        }
-
-        if field_tracker.iter().any(|f| f.is_empty()) {
-            return Err(eyre!("One field tracker ended up completely empty"));
-        }
-
-        if field_tracker.iter().all(|f| f.len() == 1) {
-            break;
-        }
-    }
+    eliminate_candidates(&mut field_tracker)?;
 
     let result = field_tracker
         .iter()
@@ -8,6 +8,8 @@ use aoc_lib::{Bench, BenchResult, Day, NoError, UserError};
 use color_eyre::eyre::Result;
 use itertools::iproduct;
 
+use super::common::GameField;
+
 pub const DAY: Day = Day {
     day: 17,
     name: "Conway Cubes",
@@ -18,7 +20,7 @@ pub const DAY: Day = Day {
 
 fn run_part1(input: &str, b: Bench) -> BenchResult {
     let state = parse::<[i8; 3]>(input).map_err(UserError)?;
-    let game = GameField::new(state, get_neighbours_3d);
+    let game = GameField::new(state, get_neighbours_3d, conway_rule);
 
     b.bench(|| {
         let mut state = game.clone();
@@ -32,7 +34,7 @@ fn run_part1(input: &str, b: Bench) -> BenchResult {
 
 fn run_part2(input: &str, b: Bench) -> BenchResult {
     let state = parse::<[i8; 4]>(input).map_err(UserError)?;
-    let game = GameField::new(state, get_neighbours_4d);
+    let game = GameField::new(state, get_neighbours_4d, conway_rule);
 
     b.bench(|| {
         let mut state = game.clone();
@@ -62,6 +64,10 @@ where
     Ok(state)
 }
 
+fn conway_rule(alive: bool, living_neighbours: usize) -> bool {
+    matches!((alive, living_neighbours), (true, 2..=3) | (false, 3))
+}
+
 const NEIGHBOUR_RANGE: RangeInclusive<i8> = -1..=1;
 
 fn get_neighbours_3d(cell: [i8; 3]) -> impl Iterator<Item = [i8; 3]> {
@@ -88,67 +94,6 @@ fn get_neighbours_4d(cell: [i8; 4]) -> impl Iterator<Item = [i8; 4]> {
     })
 }
 
-#[derive(Debug, Clone)]
-struct GameField<T, F> {
-    state: HashSet<T>,
-    buf: HashSet<T>,
-    check_buf: HashSet<T>,
-    neighbour_func: F,
-}
-
-impl<T, F, I> GameField<T, F>
-where
-    T: Copy + Eq + Hash,
-    F: Fn(T) -> I,
-    I: Iterator<Item = T>,
-{
-    fn new(state: HashSet<T>, neighbour_func: F) -> Self {
-        Self {
-            state,
-            buf: HashSet::new(),
-            check_buf: HashSet::new(),
-            neighbour_func,
-        }
-    }
-
-    fn step(&mut self) {
-        // So we don't have multiple mutable borrows through self.
-        let Self {
-            buf,
-            check_buf,
-            state,
-            ..
-        } = self;
-
-        buf.clear();
-        check_buf.clear();
-        check_buf.extend(&*state);
-
-        for node in &*state {
-            check_buf.extend((self.neighbour_func)(*node));
-        }
-
-        for &node in &*check_buf {
-            let living_neighours = (self.neighbour_func)(node)
-                .filter(|neighbour| state.contains(neighbour))
-                .take(4)
-                .count();
-
-            let alive = state.contains(&node);
-
-            if matches!((alive, living_neighours), (true, 2..=3) | (false, 3)) {
-                buf.insert(node);
-            }
-        }
-
-        std::mem::swap(&mut self.buf, &mut self.state);
-    }
-
-    fn count_active(&self) -> usize {
-        self.state.len()
-    }
-}
-
 #[cfg(test)]
 mod tests_2017 {
     use aoc_lib::Example;
@@ -162,7 +107,7 @@ mod tests_2017 {
             .open()
             .unwrap();
         let state = parse::<[i8; 3]>(&input).unwrap();
-        let mut game = GameField::new(state, get_neighbours_3d);
+        let mut game = GameField::new(state, get_neighbours_3d, conway_rule);
 
         for _ in 0..6 {
             game.step();
@@ -181,7 +126,7 @@ mod tests_2017 {
             .open()
             .unwrap();
         let state = parse::<[i8; 4]>(&input).unwrap();
-        let mut game = GameField::new(state, get_neighbours_4d);
+        let mut game = GameField::new(state, get_neighbours_4d, conway_rule);
 
         for _ in 0..6 {
             game.step();
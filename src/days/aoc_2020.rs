@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+
+use aoc_lib::{Bench, BenchResult, Day, ParseResult, UserError};
+use color_eyre::{
+    eyre::{eyre, Result},
+    Report,
+};
+
+pub const DAY: Day = Day {
+    day: 20,
+    name: "Jurassic Jigsaw",
+    part_1: run_part1,
+    part_2: Some(run_part2),
+    other: &[("Parse", run_parse)],
+};
+
+fn run_part1(input: &str, b: Bench) -> BenchResult {
+    let tiles = Tile::parse_tiles(input).map_err(UserError)?;
+
+    b.bench(|| part1(&tiles))
+}
+
+fn run_part2(input: &str, b: Bench) -> BenchResult {
+    let tiles = Tile::parse_tiles(input).map_err(UserError)?;
+
+    b.bench(|| part2(&tiles))
+}
+
+fn run_parse(input: &str, b: Bench) -> BenchResult {
+    b.bench(|| {
+        let data = Tile::parse_tiles(input)?;
+        Ok::<_, Report>(ParseResult(data))
+    })
+}
+
+const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
+
+// The 8 orientations are encoded as a u8. Bit 2 flips horizontally, the
+// lower 2 bits are the number of clockwise quarter-turns applied after that.
+const ORIENTATIONS: std::ops::Range<u8> = 0..8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Grid {
+    size: usize,
+    cells: Vec<bool>,
+}
+
+impl Grid {
+    fn parse<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Grid> {
+        let lines: Vec<_> = lines.collect();
+        let size = lines.len();
+        let mut cells = Vec::with_capacity(size * size);
+
+        for line in lines {
+            if line.chars().count() != size {
+                return Err(eyre!("Grid not square"));
+            }
+
+            for ch in line.chars() {
+                match ch {
+                    '#' => cells.push(true),
+                    '.' => cells.push(false),
+                    _ => return Err(eyre!("Unknown pixel: {}", ch)),
+                }
+            }
+        }
+
+        Ok(Grid { size, cells })
+    }
+
+    fn get(&self, x: usize, y: usize, orientation: u8) -> bool {
+        let last = self.size - 1;
+        let (mut x, mut y) = if orientation & 4 != 0 {
+            (last - x, y)
+        } else {
+            (x, y)
+        };
+
+        for _ in 0..orientation & 3 {
+            let (nx, ny) = (y, last - x);
+            x = nx;
+            y = ny;
+        }
+
+        self.cells[y * self.size + x]
+    }
+
+    fn oriented(&self, orientation: u8) -> Grid {
+        let cells = (0..self.size)
+            .flat_map(|y| (0..self.size).map(move |x| (x, y)))
+            .map(|(x, y)| self.get(x, y, orientation))
+            .collect();
+
+        Grid {
+            size: self.size,
+            cells,
+        }
+    }
+
+    fn edge(&self, orientation: u8, edge: Edge) -> u16 {
+        let last = self.size - 1;
+        (0..self.size)
+            .map(|i| match edge {
+                Edge::Top => self.get(i, 0, orientation),
+                Edge::Bottom => self.get(i, last, orientation),
+                Edge::Left => self.get(0, i, orientation),
+                Edge::Right => self.get(last, i, orientation),
+            })
+            .fold(0, |acc, px| (acc << 1) | px as u16)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tile {
+    id: u64,
+    grid: Grid,
+}
+
+impl Tile {
+    fn parse_tiles(input: &str) -> Result<Vec<Tile>> {
+        let mut tiles = Vec::new();
+        let mut lines = input.lines().map(str::trim).peekable();
+
+        while let Some(header) = lines.next() {
+            if header.is_empty() {
+                continue;
+            }
+
+            let id = header
+                .strip_prefix("Tile ")
+                .and_then(|h| h.strip_suffix(':'))
+                .ok_or_else(|| eyre!("Invalid tile header: {}", header))?
+                .parse()?;
+
+            let mut pixel_lines = Vec::new();
+            while let Some(line) = lines.next_if(|l| !l.is_empty()) {
+                pixel_lines.push(line);
+            }
+
+            let grid = Grid::parse(pixel_lines.into_iter())?;
+            if matches!(tiles.first(), Some(Tile { grid: first, .. }) if first.size != grid.size) {
+                return Err(eyre!("Tile {} is a different size", id));
+            }
+            // Tiles need a border to match on and something inside it.
+            if grid.size < 3 {
+                return Err(eyre!("Tile {} is too small", id));
+            }
+            if grid.size > 16 {
+                return Err(eyre!("Tile {} is too large", id));
+            }
+
+            tiles.push(Tile { id, grid });
+        }
+
+        Ok(tiles)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Placement {
+    tile: usize,
+    orientation: u8,
+}
+
+fn canonical_edge(edge: u16, size: usize) -> u16 {
+    let reversed = edge.reverse_bits() >> (16 - size);
+    edge.min(reversed)
+}
+
+fn assemble(tiles: &[Tile]) -> Result<(usize, Vec<Placement>)> {
+    let side = (1..=tiles.len())
+        .find(|s| s * s >= tiles.len())
+        .filter(|s| s * s == tiles.len())
+        .ok_or_else(|| eyre!("Tile count is not a square: {}", tiles.len()))?;
+    let size = tiles[0].grid.size;
+
+    let mut edge_counts: HashMap<u16, usize> = HashMap::new();
+    for tile in tiles {
+        for &edge in &[Edge::Top, Edge::Bottom, Edge::Left, Edge::Right] {
+            *edge_counts
+                .entry(canonical_edge(tile.grid.edge(0, edge), size))
+                .or_default() += 1;
+        }
+    }
+    let is_outer = |edge| edge_counts[&canonical_edge(edge, size)] == 1;
+
+    // Any corner will do, as long as it's oriented with its unmatched edges on the outside.
+    let corner = tiles
+        .iter()
+        .enumerate()
+        .flat_map(|(tile, t)| ORIENTATIONS.map(move |orientation| (tile, t, orientation)))
+        .find(|(_, t, o)| {
+            is_outer(t.grid.edge(*o, Edge::Top)) && is_outer(t.grid.edge(*o, Edge::Left))
+        })
+        .map(|(tile, _, orientation)| Placement { tile, orientation })
+        .ok_or_else(|| eyre!("Unable to find corner tile"))?;
+
+    let mut used = vec![false; tiles.len()];
+    used[corner.tile] = true;
+    let mut placements = vec![corner];
+
+    for idx in 1..tiles.len() {
+        let (row, col) = (idx / side, idx % side);
+
+        let left = (col > 0).then(|| {
+            let p = placements[idx - 1];
+            tiles[p.tile].grid.edge(p.orientation, Edge::Right)
+        });
+        let above = (row > 0).then(|| {
+            let p = placements[idx - side];
+            tiles[p.tile].grid.edge(p.orientation, Edge::Bottom)
+        });
+
+        let next = tiles
+            .iter()
+            .enumerate()
+            .filter(|(tile, _)| !used[*tile])
+            .flat_map(|(tile, t)| ORIENTATIONS.map(move |orientation| (tile, t, orientation)))
+            .find(|(_, t, o)| {
+                left.iter().all(|&e| t.grid.edge(*o, Edge::Left) == e)
+                    && above.iter().all(|&e| t.grid.edge(*o, Edge::Top) == e)
+            })
+            .map(|(tile, _, orientation)| Placement { tile, orientation })
+            .ok_or_else(|| eyre!("Unable to find tile for position ({}, {})", col, row))?;
+
+        used[next.tile] = true;
+        placements.push(next);
+    }
+
+    Ok((side, placements))
+}
+
+fn build_image(tiles: &[Tile], side: usize, placements: &[Placement]) -> Grid {
+    let inner = tiles[0].grid.size - 2;
+    let size = side * inner;
+    let mut cells = vec![false; size * size];
+
+    for (idx, placement) in placements.iter().enumerate() {
+        let (row, col) = (idx / side, idx % side);
+        let grid = &tiles[placement.tile].grid;
+
+        for y in 0..inner {
+            for x in 0..inner {
+                let px = grid.get(x + 1, y + 1, placement.orientation);
+                cells[(row * inner + y) * size + col * inner + x] = px;
+            }
+        }
+    }
+
+    Grid { size, cells }
+}
+
+fn part1(tiles: &[Tile]) -> Result<u64> {
+    let (side, placements) = assemble(tiles)?;
+
+    let corners = [0, side - 1, side * (side - 1), side * side - 1];
+    Ok(corners
+        .iter()
+        .map(|&idx| tiles[placements[idx].tile].id)
+        .product())
+}
+
+fn part2(tiles: &[Tile]) -> Result<usize> {
+    let (side, placements) = assemble(tiles)?;
+    let image = build_image(tiles, side, &placements);
+
+    let monster: Vec<(usize, usize)> = SEA_MONSTER
+        .iter()
+        .zip(0..)
+        .flat_map(|(line, y)| {
+            line.chars()
+                .zip(0..)
+                .filter(|(c, _)| *c == '#')
+                .map(move |(_, x)| (x, y))
+        })
+        .collect();
+    let monster_width = SEA_MONSTER[0].len();
+    let monster_height = SEA_MONSTER.len();
+
+    if image.size < monster_width || image.size < monster_height {
+        return Err(eyre!("Image too small to contain sea monsters"));
+    }
+
+    for orientation in ORIENTATIONS {
+        let image = image.oriented(orientation);
+        let mut monster_cells = vec![false; image.cells.len()];
+        let mut found = false;
+
+        for y in 0..=image.size - monster_height {
+            for x in 0..=image.size - monster_width {
+                let idx = |(mx, my): (usize, usize)| (y + my) * image.size + x + mx;
+                if monster.iter().all(|&m| image.cells[idx(m)]) {
+                    found = true;
+                    monster.iter().for_each(|&m| monster_cells[idx(m)] = true);
+                }
+            }
+        }
+
+        if found {
+            let roughness = image
+                .cells
+                .iter()
+                .zip(&monster_cells)
+                .filter(|(px, monster)| **px && !**monster)
+                .count();
+            return Ok(roughness);
+        }
+    }
+
+    Err(eyre!("No sea monsters found"))
+}
+
+#[cfg(test)]
+mod tests_2020 {
+    use aoc_lib::Example;
+
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let input = aoc_lib::input(20)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let tiles = Tile::parse_tiles(&input).unwrap();
+
+        let expected = [2311, 1951, 1171, 1427, 1489, 2473, 2971, 2729, 3079];
+        let actual: Vec<_> = tiles.iter().map(|t| t.id).collect();
+
+        assert_eq!(expected[..], actual[..]);
+        assert!(tiles.iter().all(|t| t.grid.size == 10));
+    }
+
+    #[test]
+    fn parse_error_test() {
+        let tests = [
+            ("Tile 1:\n", "Tile 1 is too small"),
+            ("Tile 1:\n#", "Tile 1 is too small"),
+            ("Tile 1:\n#.\n.#", "Tile 1 is too small"),
+            ("Tile 1:\n#..\n.#\n..#.", "Grid not square"),
+            ("Tile 1:\n#..\n.#.\n..#.", "Grid not square"),
+            ("Tile 1:\n#..\n.x.\n..#", "Unknown pixel: x"),
+            (
+                "Tile 1:\n#..\n.#.\n..#\n\nTile 2:\n#...\n....\n....\n....",
+                "Tile 2 is a different size",
+            ),
+            ("Tile x:\n#..\n.#.\n..#", "invalid digit found in string"),
+            ("Tile 1\n#..\n.#.\n..#", "Invalid tile header: Tile 1"),
+        ];
+
+        for (input, expected) in &tests {
+            let err = Tile::parse_tiles(input).unwrap_err();
+            assert_eq!(err.to_string(), *expected, "{:?}", input);
+        }
+
+        assert!(Tile::parse_tiles("Tile 1:\n#..\n.#.\n..#").is_ok());
+    }
+
+    #[test]
+    fn orientation_test() {
+        let grid = Grid::parse(["#..", "...", "..."].iter().copied()).unwrap();
+
+        let corners: Vec<_> = ORIENTATIONS
+            .map(|o| {
+                let g = grid.oriented(o);
+                g.cells.iter().position(|px| *px).unwrap()
+            })
+            .collect();
+
+        // Every corner should be reachable, twice each.
+        for corner in &[0, 2, 6, 8] {
+            assert_eq!(corners.iter().filter(|c| *c == corner).count(), 2);
+        }
+    }
+
+    #[test]
+    fn part1_example() {
+        let input = aoc_lib::input(20)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let tiles = Tile::parse_tiles(&input).unwrap();
+
+        let expected = 20899048083289;
+        let actual = part1(&tiles).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = aoc_lib::input(20)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let tiles = Tile::parse_tiles(&input).unwrap();
+
+        let expected = 273;
+        let actual = part2(&tiles).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+}
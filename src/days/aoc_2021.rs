@@ -0,0 +1,224 @@
+use std::collections::HashSet;
+
+use aoc_lib::{parsers::split_pair, Bench, BenchResult, Day, ParseResult, UserError};
+use color_eyre::{
+    eyre::{eyre, Result},
+    Report,
+};
+use itertools::Itertools;
+
+use super::common::eliminate_candidates;
+
+pub const DAY: Day = Day {
+    day: 21,
+    name: "Allergen Assessment",
+    part_1: run_part1,
+    part_2: Some(run_part2),
+    other: &[("Parse", run_parse)],
+};
+
+fn run_part1(input: &str, b: Bench) -> BenchResult {
+    let foods: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Food::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+
+    b.bench(|| part1(&foods))
+}
+
+fn run_part2(input: &str, b: Bench) -> BenchResult {
+    let foods: Vec<_> = input
+        .lines()
+        .map(str::trim)
+        .map(Food::parse)
+        .collect::<Result<_, _>>()
+        .map_err(UserError)?;
+
+    b.bench(|| part2(&foods))
+}
+
+fn run_parse(input: &str, b: Bench) -> BenchResult {
+    b.bench(|| {
+        let data: Vec<_> = input
+            .lines()
+            .map(str::trim)
+            .map(Food::parse)
+            .collect::<Result<_, _>>()?;
+        Ok::<_, Report>(ParseResult(data))
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct Food<'a> {
+    ingredients: Vec<&'a str>,
+    allergens: Vec<&'a str>,
+}
+
+impl<'a> Food<'a> {
+    fn parse(line: &'a str) -> Result<Food<'a>> {
+        let (ingredients, allergens) = match line.strip_suffix(')') {
+            Some(line) => split_pair(line, " (contains ")?,
+            None => (line, ""),
+        };
+
+        let ingredients: Vec<_> = ingredients.split_whitespace().collect();
+        let allergens = allergens
+            .split(',')
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .collect();
+
+        if ingredients.is_empty() {
+            return Err(eyre!("Food has no ingredients: {}", line));
+        }
+
+        Ok(Food {
+            ingredients,
+            allergens,
+        })
+    }
+}
+
+// Returns the set of ingredients that could contain each allergen, ordered by allergen name.
+fn allergen_candidates<'a>(foods: &[Food<'a>]) -> Vec<HashSet<&'a str>> {
+    foods
+        .iter()
+        .flat_map(|f| f.allergens.iter().copied())
+        .sorted_unstable()
+        .dedup()
+        .map(|allergen| {
+            let mut containing = foods.iter().filter(|f| f.allergens.contains(&allergen));
+            // There's always at least one food, otherwise we wouldn't know about the allergen.
+            let mut candidates: HashSet<_> = containing
+                .next()
+                .map(|f| f.ingredients.iter().copied().collect())
+                .unwrap_or_default();
+
+            for food in containing {
+                candidates.retain(|i| food.ingredients.contains(i));
+            }
+
+            candidates
+        })
+        .collect()
+}
+
+fn part1(foods: &[Food]) -> Result<usize> {
+    let candidates = allergen_candidates(foods);
+
+    let count = foods
+        .iter()
+        .flat_map(|f| &f.ingredients)
+        .filter(|i| !candidates.iter().any(|c| c.contains(*i)))
+        .count();
+
+    Ok(count)
+}
+
+fn part2(foods: &[Food]) -> Result<String> {
+    let mut candidates = allergen_candidates(foods);
+
+    eliminate_candidates(&mut candidates)?;
+
+    Ok(candidates.iter().filter_map(|c| c.iter().next()).join(","))
+}
+
+#[cfg(test)]
+mod tests_2021 {
+    use aoc_lib::Example;
+
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let input = aoc_lib::input(21)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+
+        let expected = vec![
+            Food {
+                ingredients: vec!["mxmxvkd", "kfcds", "sqjhc", "nhms"],
+                allergens: vec!["dairy", "fish"],
+            },
+            Food {
+                ingredients: vec!["trh", "fvjkl", "sbzzf", "mxmxvkd"],
+                allergens: vec!["dairy"],
+            },
+            Food {
+                ingredients: vec!["sqjhc", "fvjkl"],
+                allergens: vec!["soy"],
+            },
+            Food {
+                ingredients: vec!["sqjhc", "mxmxvkd", "sbzzf"],
+                allergens: vec!["fish"],
+            },
+        ];
+
+        let actual: Vec<_> = input
+            .lines()
+            .map(str::trim)
+            .map(Food::parse)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn part1_example() {
+        let input = aoc_lib::input(21)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let foods: Vec<_> = input
+            .lines()
+            .map(str::trim)
+            .map(Food::parse)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        let expected = 5;
+        let actual = part1(&foods).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = aoc_lib::input(21)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let foods: Vec<_> = input
+            .lines()
+            .map(str::trim)
+            .map(Food::parse)
+            .collect::<Result<_>>()
+            .unwrap();
+
+        let expected = "mxmxvkd,sqjhc,fvjkl";
+        let actual = part2(&foods).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn eliminate_test() {
+        let mut candidates = vec![
+            ["a", "b"].iter().copied().collect::<HashSet<_>>(),
+            ["a"].iter().copied().collect(),
+            ["a", "b", "c"].iter().copied().collect(),
+        ];
+
+        eliminate_candidates(&mut candidates).unwrap();
+
+        let actual: Vec<_> = candidates
+            .iter()
+            .map(|c| *c.iter().next().unwrap())
+            .collect();
+        assert_eq!(actual, ["b", "a", "c"]);
+    }
+}
@@ -0,0 +1,149 @@
+use aoc_lib::{Bench, BenchResult, Day, UserError};
+use color_eyre::eyre::{eyre, Result};
+use itertools::Itertools;
+
+pub const DAY: Day = Day {
+    day: 23,
+    name: "Crab Cups",
+    part_1: run_part1,
+    part_2: Some(run_part2),
+    other: &[],
+};
+
+fn run_part1(input: &str, b: Bench) -> BenchResult {
+    let cups = parse(input).map_err(UserError)?;
+
+    b.bench(|| part1(&cups, 100))
+}
+
+fn run_part2(input: &str, b: Bench) -> BenchResult {
+    let cups = parse(input).map_err(UserError)?;
+
+    b.bench(|| part2(&cups))
+}
+
+fn parse(input: &str) -> Result<Vec<u32>> {
+    let cups: Vec<_> = input
+        .trim()
+        .chars()
+        .map(|c| c.to_digit(10).ok_or_else(|| eyre!("Invalid cup: {}", c)))
+        .collect::<Result<_>>()?;
+
+    if cups.len() < 4 {
+        return Err(eyre!("Need at least 4 cups"));
+    }
+
+    let is_permutation = cups
+        .iter()
+        .sorted_unstable()
+        .zip(1..)
+        .all(|(&cup, expected)| cup == expected);
+    if !is_permutation {
+        return Err(eyre!("Cups must be labelled 1 to {}", cups.len()));
+    }
+
+    Ok(cups)
+}
+
+// The cups are stored as a ring of labels, where `next[label]` is the label of the cup
+// clockwise of it. Index 0 is unused.
+fn play_game(cups: &[u32], total_cups: u32, moves: usize) -> Vec<u32> {
+    let mut next = vec![0; total_cups as usize + 1];
+
+    let first = cups[0];
+    let last = cups
+        .iter()
+        .copied()
+        .chain(cups.len() as u32 + 1..=total_cups)
+        .fold(first, |prev, label| {
+            next[prev as usize] = label;
+            label
+        });
+    next[last as usize] = first;
+
+    let mut current = first;
+    for _ in 0..moves {
+        let a = next[current as usize];
+        let b = next[a as usize];
+        let c = next[b as usize];
+        next[current as usize] = next[c as usize];
+
+        let mut dest = current;
+        loop {
+            dest = if dest == 1 { total_cups } else { dest - 1 };
+            if dest != a && dest != b && dest != c {
+                break;
+            }
+        }
+
+        next[c as usize] = next[dest as usize];
+        next[dest as usize] = a;
+        current = next[current as usize];
+    }
+
+    next
+}
+
+fn part1(cups: &[u32], moves: usize) -> Result<String> {
+    let next = play_game(cups, cups.len() as u32, moves);
+
+    let labels = std::iter::successors(Some(next[1]), |&cup| Some(next[cup as usize]))
+        .take_while(|&cup| cup != 1)
+        .join("");
+
+    Ok(labels)
+}
+
+fn part2(cups: &[u32]) -> Result<u64> {
+    let next = play_game(cups, 1_000_000, 10_000_000);
+
+    let first = next[1];
+    let second = next[first as usize];
+
+    Ok(first as u64 * second as u64)
+}
+
+#[cfg(test)]
+mod tests_2023 {
+    use aoc_lib::Example;
+
+    use super::*;
+
+    #[test]
+    fn part1_example() {
+        let input = aoc_lib::input(23)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let cups = parse(&input).unwrap();
+
+        let tests = [(10, "92658374"), (100, "67384529")];
+
+        for (moves, expected) in &tests {
+            let actual = part1(&cups, *moves).unwrap();
+            assert_eq!(actual, *expected, "{}", moves);
+        }
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = aoc_lib::input(23)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let cups = parse(&input).unwrap();
+
+        let expected = 149245887792;
+        let actual = part2(&cups).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn parse_test() {
+        assert!(parse("389125467").is_ok());
+        assert!(parse("38912546").is_err());
+        assert!(parse("389125461").is_err());
+        assert!(parse("12").is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashSet, ops::Add};
+
+use aoc_lib::{Bench, BenchResult, Day, NoError, ParseResult, UserError};
+use color_eyre::{
+    eyre::{eyre, Result},
+    Report,
+};
+
+use super::common::GameField;
+
+pub const DAY: Day = Day {
+    day: 24,
+    name: "Lobby Layout",
+    part_1: run_part1,
+    part_2: Some(run_part2),
+    other: &[("Parse", run_parse)],
+};
+
+fn run_part1(input: &str, b: Bench) -> BenchResult {
+    let paths = parse_paths(input).map_err(UserError)?;
+
+    b.bench(|| Ok::<_, NoError>(flip_tiles(&paths).len()))
+}
+
+fn run_part2(input: &str, b: Bench) -> BenchResult {
+    let paths = parse_paths(input).map_err(UserError)?;
+    let game = GameField::new(flip_tiles(&paths), Hex::neighbours, hex_rule);
+
+    b.bench(|| {
+        let mut game = game.clone();
+        for _ in 0..100 {
+            game.step();
+        }
+
+        Ok::<_, NoError>(game.count_active())
+    })
+}
+
+fn run_parse(input: &str, b: Bench) -> BenchResult {
+    b.bench(|| {
+        let data = parse_paths(input)?;
+        Ok::<_, Report>(ParseResult(data))
+    })
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Direction {
+    East,
+    SouthEast,
+    SouthWest,
+    West,
+    NorthWest,
+    NorthEast,
+}
+
+impl Direction {
+    const ALL: [Direction; 6] = [
+        Direction::East,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+        Direction::NorthEast,
+    ];
+
+    fn parse_path(line: &str) -> Result<Vec<Direction>> {
+        let mut path = Vec::new();
+        let mut chars = line.chars();
+
+        while let Some(ch) = chars.next() {
+            let dir = match ch {
+                'e' => Direction::East,
+                'w' => Direction::West,
+                'n' | 's' => match (ch, chars.next()) {
+                    ('s', Some('e')) => Direction::SouthEast,
+                    ('s', Some('w')) => Direction::SouthWest,
+                    ('n', Some('w')) => Direction::NorthWest,
+                    ('n', Some('e')) => Direction::NorthEast,
+                    _ => return Err(eyre!("Invalid direction in `{}`", line)),
+                },
+                _ => return Err(eyre!("Unexpected character `{}` in `{}`", ch, line)),
+            };
+
+            path.push(dir);
+        }
+
+        Ok(path)
+    }
+}
+
+// Axial coordinates, with q increasing to the east and r increasing to the south-east.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+struct Hex {
+    q: i32,
+    r: i32,
+}
+
+impl Hex {
+    fn neighbours(self) -> impl Iterator<Item = Hex> {
+        Direction::ALL.iter().map(move |&dir| self + dir)
+    }
+}
+
+impl Add<Direction> for Hex {
+    type Output = Hex;
+
+    fn add(self, dir: Direction) -> Hex {
+        let (dq, dr) = match dir {
+            Direction::East => (1, 0),
+            Direction::SouthEast => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (0, -1),
+            Direction::NorthEast => (1, -1),
+        };
+
+        Hex {
+            q: self.q + dq,
+            r: self.r + dr,
+        }
+    }
+}
+
+fn parse_paths(input: &str) -> Result<Vec<Vec<Direction>>> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(Direction::parse_path)
+        .collect()
+}
+
+fn flip_tiles(paths: &[Vec<Direction>]) -> HashSet<Hex> {
+    let mut black_tiles = HashSet::new();
+
+    for path in paths {
+        let tile = path.iter().fold(Hex::default(), |hex, &dir| hex + dir);
+        if !black_tiles.remove(&tile) {
+            black_tiles.insert(tile);
+        }
+    }
+
+    black_tiles
+}
+
+fn hex_rule(black: bool, black_neighbours: usize) -> bool {
+    matches!((black, black_neighbours), (true, 1..=2) | (false, 2))
+}
+
+#[cfg(test)]
+mod tests_2024 {
+    use aoc_lib::Example;
+
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        use Direction::*;
+
+        let tests = [
+            ("esenee", vec![East, SouthEast, NorthEast, East]),
+            ("nwwswee", vec![NorthWest, West, SouthWest, East, East]),
+        ];
+
+        for (input, expected) in &tests {
+            assert_eq!(
+                Direction::parse_path(input).unwrap(),
+                *expected,
+                "{}",
+                input
+            );
+        }
+
+        assert!(Direction::parse_path("nw x").is_err());
+        assert!(Direction::parse_path("ees").is_err());
+    }
+
+    #[test]
+    fn walk_test() {
+        let tests = [("esew", Hex { q: 0, r: 1 }), ("nwwswee", Hex::default())];
+
+        for (input, expected) in &tests {
+            let path = Direction::parse_path(input).unwrap();
+            let actual = path.iter().fold(Hex::default(), |hex, &dir| hex + dir);
+            assert_eq!(actual, *expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn part1_example() {
+        let input = aoc_lib::input(24)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let paths = parse_paths(&input).unwrap();
+
+        let expected = 10;
+        let actual = flip_tiles(&paths).len();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn part2_example() {
+        let input = aoc_lib::input(24)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let paths = parse_paths(&input).unwrap();
+        let mut game = GameField::new(flip_tiles(&paths), Hex::neighbours, hex_rule);
+
+        let tests = [(1, 15), (2, 12), (10, 37), (100, 2208)];
+        let mut day = 0;
+
+        for (until, expected) in &tests {
+            while day < *until {
+                game.step();
+                day += 1;
+            }
+
+            assert_eq!(game.count_active(), *expected, "{}", day);
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use aoc_lib::{Bench, BenchResult, Day, UserError};
+use color_eyre::eyre::{eyre, Result};
+
+pub const DAY: Day = Day {
+    day: 25,
+    name: "Combo Breaker",
+    part_1: run_part1,
+    part_2: None,
+    other: &[],
+};
+
+fn run_part1(input: &str, b: Bench) -> BenchResult {
+    let (card_key, door_key) = parse_input(input).map_err(UserError)?;
+
+    b.bench(|| part1(card_key, door_key))
+}
+
+const MODULUS: u64 = 20201227;
+const SUBJECT: u64 = 7;
+
+fn parse_input(input: &str) -> Result<(u64, u64)> {
+    let mut keys = input.lines().map(str::trim).filter(|l| !l.is_empty());
+
+    let mut next_key = |name| -> Result<u64> {
+        let key = keys
+            .next()
+            .ok_or_else(|| eyre!("Missing {} public key", name))?
+            .parse()?;
+
+        if key >= MODULUS {
+            Err(eyre!("Invalid {} public key: {}", name, key))
+        } else {
+            Ok(key)
+        }
+    };
+
+    let card_key = next_key("card")?;
+    let door_key = next_key("door")?;
+
+    Ok((card_key, door_key))
+}
+
+fn mod_pow(mut base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let mut result = 1;
+    base %= modulus;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+
+    result
+}
+
+// Baby-step giant-step. Finds the smallest x where base^x = target (mod modulus),
+// assuming the modulus is prime.
+fn discrete_log(base: u64, target: u64, modulus: u64) -> Option<u64> {
+    let steps = (1..).find(|s| s * s >= modulus - 1)?;
+
+    let mut baby_steps = HashMap::with_capacity(steps as usize);
+    let mut value = 1;
+    for j in 0..steps {
+        baby_steps.entry(value).or_insert(j);
+        value = value * base % modulus;
+    }
+
+    // base^-steps, by Fermat's little theorem.
+    let giant_step = mod_pow(base, modulus - 1 - steps, modulus);
+
+    let mut gamma = target % modulus;
+    for i in 0..steps {
+        if let Some(j) = baby_steps.get(&gamma) {
+            return Some(i * steps + j);
+        }
+        gamma = gamma * giant_step % modulus;
+    }
+
+    None
+}
+
+fn part1(card_key: u64, door_key: u64) -> Result<u64> {
+    let card_loop = discrete_log(SUBJECT, card_key, MODULUS)
+        .ok_or_else(|| eyre!("Unable to find card loop size"))?;
+
+    Ok(mod_pow(door_key, card_loop, MODULUS))
+}
+
+#[cfg(test)]
+mod tests_2025 {
+    use aoc_lib::Example;
+
+    use super::*;
+
+    #[test]
+    fn loop_size_test() {
+        let tests = [(5764801, 8), (17807724, 11)];
+
+        for (key, expected) in &tests {
+            let actual = discrete_log(SUBJECT, *key, MODULUS).unwrap();
+            assert_eq!(actual, *expected, "{}", key);
+            assert_eq!(mod_pow(SUBJECT, actual, MODULUS), *key, "{}", key);
+        }
+    }
+
+    #[test]
+    fn part1_example() {
+        let input = aoc_lib::input(25)
+            .example(Example::Part1, 1)
+            .open()
+            .unwrap();
+        let (card_key, door_key) = parse_input(&input).unwrap();
+
+        let expected = 14897079;
+
+        assert_eq!(part1(card_key, door_key).unwrap(), expected);
+        assert_eq!(part1(door_key, card_key).unwrap(), expected);
+    }
+}
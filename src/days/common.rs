@@ -0,0 +1,211 @@
+use std::{collections::HashSet, hash::Hash};
+
+use color_eyre::eyre::{eyre, Result};
+
+// Repeatedly takes any slot that has been narrowed down to a single candidate, and
+// removes that candidate from every other slot, until every slot has exactly one.
+pub fn eliminate_candidates<T>(candidates: &mut [HashSet<T>]) -> Result<()>
+where
+    T: Copy + Eq + Hash,
+{
+    loop {
+        if candidates.iter().any(|c| c.is_empty()) {
+            return Err(eyre!("A candidate set ended up completely empty"));
+        }
+
+        if candidates.iter().all(|c| c.len() == 1) {
+            return Ok(());
+        }
+
+        let mut did_advance = false;
+
+        for i in 0..candidates.len() {
+            if candidates[i].len() != 1 {
+                continue;
+            }
+            let entry = *candidates[i].iter().next().unwrap();
+
+            candidates
+                .iter_mut()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .for_each(|(_, c)| did_advance |= c.remove(&entry));
+        }
+
+        if !did_advance {
+            return Err(eyre!("Didn't advance filter"));
+        }
+    }
+}
+
+// Decides whether a cell is alive in the next step, given whether it's currently alive
+// and how many of its neighbours are. The neighbour count is capped at 4.
+pub type LifeRule = fn(bool, usize) -> bool;
+
+#[derive(Debug, Clone)]
+pub struct GameField<T, F> {
+    state: HashSet<T>,
+    buf: HashSet<T>,
+    check_buf: HashSet<T>,
+    neighbour_func: F,
+    rule: LifeRule,
+}
+
+impl<T, F, I> GameField<T, F>
+where
+    T: Copy + Eq + Hash,
+    F: Fn(T) -> I,
+    I: Iterator<Item = T>,
+{
+    pub fn new(state: HashSet<T>, neighbour_func: F, rule: LifeRule) -> Self {
+        Self {
+            state,
+            buf: HashSet::new(),
+            check_buf: HashSet::new(),
+            neighbour_func,
+            rule,
+        }
+    }
+
+    pub fn step(&mut self) {
+        // So we don't have multiple mutable borrows through self.
+        let Self {
+            buf,
+            check_buf,
+            state,
+            ..
+        } = self;
+
+        buf.clear();
+        check_buf.clear();
+        check_buf.extend(&*state);
+
+        for node in &*state {
+            check_buf.extend((self.neighbour_func)(*node));
+        }
+
+        for &node in &*check_buf {
+            let living_neighours = (self.neighbour_func)(node)
+                .filter(|neighbour| state.contains(neighbour))
+                .take(4)
+                .count();
+
+            let alive = state.contains(&node);
+
+            if (self.rule)(alive, living_neighours) {
+                buf.insert(node);
+            }
+        }
+
+        std::mem::swap(&mut self.buf, &mut self.state);
+    }
+
+    pub fn count_active(&self) -> usize {
+        self.state.len()
+    }
+}
+
+// Quotes a string for use in JSON output, escaping anything that needs it.
+pub fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+// Splits the input into records separated by blank lines, where a blank line may still
+// contain whitespace. Works with both `\n` and `\r\n` line endings. Each record is a slice
+// of the input running from the start of its first line to the end of its last line, and
+// runs of several blank lines never produce empty records.
+pub fn split_records(input: &str) -> impl Iterator<Item = &str> {
+    let mut lines = input
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line))
+        })
+        .peekable();
+
+    let is_blank = |line: &str| line.trim().is_empty();
+    let line_end =
+        |start: usize, line: &str| start + line.trim_end_matches(&['\r', '\n'][..]).len();
+
+    std::iter::from_fn(move || {
+        let (start, first) = loop {
+            let (start, line) = lines.next()?;
+            if !is_blank(line) {
+                break (start, line);
+            }
+        };
+
+        let mut end = line_end(start, first);
+        while let Some((next_start, next)) = lines.next_if(|&(_, line)| !is_blank(line)) {
+            end = line_end(next_start, next);
+        }
+
+        Some(&input[start..end])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_records_test() {
+        let expected = ["a b\nc", "d", "e\nf"];
+
+        let tests = [
+            "a b\nc\n\nd\n\ne\nf",
+            "a b\nc\n\nd\n\ne\nf\n",
+            "\n\na b\nc\n\n\n\nd\n\ne\nf\n\n\n",
+            "a b\nc\n  \nd\n\t\ne\nf\n \t \n",
+        ];
+        for input in &tests {
+            assert_eq!(
+                split_records(input).collect::<Vec<_>>(),
+                expected,
+                "{:?}",
+                input
+            );
+        }
+
+        let expected = ["a b\r\nc", "d", "e\r\nf"];
+
+        let tests = [
+            "a b\r\nc\r\n\r\nd\r\n\r\ne\r\nf",
+            "a b\r\nc\r\n\r\nd\r\n\r\ne\r\nf\r\n\r\n\r\n",
+            "\r\na b\r\nc\r\n \r\n\r\nd\r\n\t\r\ne\r\nf\r\n",
+        ];
+        for input in &tests {
+            assert_eq!(
+                split_records(input).collect::<Vec<_>>(),
+                expected,
+                "{:?}",
+                input
+            );
+        }
+
+        // Records are slices of the input, so lines within them keep their own endings.
+        let mixed = "a\r\nb\n\r\nc\n";
+        assert_eq!(split_records(mixed).collect::<Vec<_>>(), ["a\r\nb", "c"]);
+
+        for input in &["", "\n", "\r\n\r\n", " \n\t\r\n  "] {
+            assert_eq!(split_records(input).count(), 0, "{:?}", input);
+        }
+    }
+}